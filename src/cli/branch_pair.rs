@@ -0,0 +1,243 @@
+use std::path::Path;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+use pair_commit_tool::models::author::author_collection::AuthorCollection;
+use pair_commit_tool::models::branch_pair::{BranchPair, BranchPairCollection};
+
+use crate::cli::store::Store;
+use crate::cli::team::member_references;
+use crate::cli::{exit_with_error, lock_or_exit, required_value};
+use crate::error::Error;
+use crate::git;
+use crate::persistence;
+
+pub enum BranchPairSubCommands {
+    Set,
+    Clear,
+    List,
+}
+
+impl BranchPairSubCommands {
+    pub fn get_string(&self) -> &str {
+        match self {
+            BranchPairSubCommands::Set => "set",
+            BranchPairSubCommands::Clear => "clear",
+            BranchPairSubCommands::List => "list",
+        }
+    }
+}
+
+fn branch_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("branch")
+        .required(true)
+        .value_name("BRANCH")
+        .help("Branch name, or a pattern where * matches anything and ? a single character")
+}
+
+pub fn branch_pair_sub_command<'a, 'b>(name: &'a str) -> App<'a, 'b> {
+    SubCommand::with_name(name)
+        .about("Manage co-authors that are used automatically on a branch")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name(BranchPairSubCommands::Set.get_string())
+                .about("Bind co-authors to a branch, replacing any already bound to it")
+                .arg(branch_arg())
+                .arg(
+                    Arg::with_name("author")
+                        .required(true)
+                        .multiple(true)
+                        .value_name("AUTHOR")
+                        .help("Index, alias or email of the co-authors"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(BranchPairSubCommands::Clear.get_string())
+                .about("Stop using bound co-authors on a branch")
+                .arg(branch_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name(BranchPairSubCommands::List.get_string())
+                .about("List branches with their co-authors"),
+        )
+}
+
+pub fn handle_branch_pair_sub_command(
+    authors: &AuthorCollection,
+    matches: &ArgMatches,
+    store: &Store,
+) {
+    let file_path = store.branch_pairs_file_path().unwrap_or_else(|| {
+        exit_with_error(Error::Git(String::from(
+            "branch-pair needs to be run inside a git repository",
+        )))
+    });
+    let _lock = lock_or_exit(&file_path);
+    let mut pairs = load_branch_pairs(&file_path);
+    if let Some(set_matches) = matches.subcommand_matches(BranchPairSubCommands::Set.get_string()) {
+        let branch = required_value(set_matches, "branch");
+        pairs.set(BranchPair::new(
+            branch,
+            member_references(authors, set_matches),
+        ));
+    } else if let Some(clear_matches) =
+        matches.subcommand_matches(BranchPairSubCommands::Clear.get_string())
+    {
+        let branch = required_value(clear_matches, "branch");
+        if pairs.clear(branch).is_none() {
+            exit_with_error(format!("No co-authors are bound to '{}'", branch));
+        }
+    } else if let Some(_list_matches) =
+        matches.subcommand_matches(BranchPairSubCommands::List.get_string())
+    {
+        let current_branch = git::current_branch().ok();
+        println!(
+            "{}",
+            describe_branch_pairs(authors, &pairs, current_branch.as_deref())
+        );
+        return;
+    }
+    persistence::save_branch_pairs(file_path, &pairs).unwrap_or_else(|e| exit_with_error(e));
+}
+
+fn load_branch_pairs(file_path: &Path) -> BranchPairCollection {
+    persistence::load_branch_pairs(file_path.to_path_buf()).unwrap_or_else(|e| exit_with_error(e))
+}
+
+/// Makes the bindings of the repository refer to other authors, given as pairs of the old and the
+/// new reference, such as when authors are merged
+pub fn replace_branch_pair_references(store: &Store, replaced: &[(String, String)]) {
+    let file_path = match store.branch_pairs_file_path() {
+        Some(file_path) if file_path.is_file() => file_path,
+        _ => return,
+    };
+    let _lock = lock_or_exit(&file_path);
+    let mut pairs = load_branch_pairs(&file_path);
+    let mut changed = false;
+    for (from, to) in replaced {
+        changed |= pairs.replace_reference(from, to);
+    }
+    if changed {
+        persistence::save_branch_pairs(file_path, &pairs).unwrap_or_else(|e| exit_with_error(e));
+    }
+}
+
+/// The current branch with the co-authors bound to it, or `None` when the branch has none or
+/// `HEAD` is detached
+pub fn current_branch_pair(store: &Store) -> Option<(String, BranchPair)> {
+    let file_path = store.branch_pairs_file_path()?;
+    let branch = git::current_branch().ok()?;
+    let pair = load_branch_pairs(&file_path).find_for(&branch)?.clone();
+    Some((branch, pair))
+}
+
+/// The authors with exactly the co-authors bound to the branch active
+pub fn branch_pair_authors(mut authors: AuthorCollection, pair: &BranchPair) -> AuthorCollection {
+    let indexes: Vec<usize> = pair
+        .members()
+        .iter()
+        .filter_map(|reference| authors.find_author_index_by_reference(reference))
+        .collect();
+    authors.set_active_authors_by_indexes(&indexes);
+    authors
+}
+
+pub fn describe_branch_pair(branch: &str, pair: &BranchPair) -> String {
+    if pair.branch() == branch {
+        format!("Using the co-authors bound to branch {}", branch)
+    } else {
+        format!(
+            "Using the co-authors bound to '{}' on branch {}",
+            pair.branch(),
+            branch
+        )
+    }
+}
+
+fn describe_branch_pairs(
+    authors: &AuthorCollection,
+    pairs: &BranchPairCollection,
+    current_branch: Option<&str>,
+) -> String {
+    let current = current_branch.and_then(|branch| pairs.find_for(branch));
+    pairs
+        .pairs()
+        .iter()
+        .map(|pair| {
+            let members = pair
+                .members()
+                .iter()
+                .map(
+                    |reference| match authors.find_author_index_by_reference(reference) {
+                        Some(index) => format!("\n    - {}", authors.authors()[index]),
+                        None => format!("\n    - {} (unknown)", reference),
+                    },
+                )
+                .collect::<String>();
+            let marker = if current == Some(pair) {
+                " (current)"
+            } else {
+                ""
+            };
+            format!(
+                "- branch: {}{}\n  members:{}",
+                pair.branch(),
+                marker,
+                members
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n---\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    use pair_commit_tool::models::author::ActiveState::ACTIVE;
+    use pair_commit_tool::models::author::Author;
+    use pair_commit_tool::models::branch_pair::{BranchPair, BranchPairCollection};
+
+    use crate::cli::branch_pair::{branch_pair_authors, describe_branch_pairs};
+
+    fn authors() -> AuthorCollection {
+        let mut authors = AuthorCollection::new();
+        authors.add_author(Author::with_active_state(
+            "Tester",
+            "tester@test.com",
+            ACTIVE,
+        ));
+        authors.add_author(Author::new("Other", "other@test.com"));
+        authors
+    }
+
+    fn pair(authors: &AuthorCollection) -> BranchPair {
+        BranchPair::new(
+            "feature/*",
+            vec![authors.authors()[1].reference().clone(), "gone".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_branch_pair_authors_replace_active() {
+        let authors = authors();
+        let pair = pair(&authors);
+        let active = branch_pair_authors(authors, &pair);
+        assert_eq!(1, active.active_authors().len());
+        assert_eq!("Other", active.active_authors()[0].name());
+    }
+
+    #[test]
+    fn test_describe_branch_pairs() {
+        let authors = authors();
+        let pairs = BranchPairCollection::from(vec![pair(&authors)]);
+        let expected = "- branch: feature/* (current)\n  \
+                        members:\n    \
+                        - Other <other@test.com>\n    \
+                        - gone (unknown)";
+        assert_eq!(
+            expected,
+            describe_branch_pairs(&authors, &pairs, Some("feature/login"))
+        );
+        assert!(!describe_branch_pairs(&authors, &pairs, Some("main")).contains("(current)"));
+    }
+}
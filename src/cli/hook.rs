@@ -0,0 +1,443 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use pair_commit_tool::models::author::author_collection::AuthorCollection;
+use pair_commit_tool::models::commit_message::{CommitMessage, CommitSource, DEFAULT_COMMENT_CHAR};
+use pair_commit_tool::models::settings::{HookPolicy, Settings, TrailerSettings};
+
+use crate::cli::exit_with_error;
+use crate::error::Error;
+use crate::git;
+
+const HOOK_NAME: &str = "prepare-commit-msg";
+const CHAINED_HOOK_NAME: &str = "prepare-commit-msg.pair-commit-tool-chained";
+const HOOK_MARKER: &str = "# Installed by pair-commit-tool";
+
+pub enum HookSubCommands {
+    PrepareCommitMsg,
+}
+
+impl HookSubCommands {
+    pub fn get_string(&self) -> &str {
+        match self {
+            HookSubCommands::PrepareCommitMsg => "prepare-commit-msg",
+        }
+    }
+}
+
+/// Updates the co-author trailers of the message file, returning whether it credits co-authors
+/// of this run rather than being left alone
+pub fn handle_prepare_commit_msg(
+    authors: AuthorCollection,
+    settings: &Settings,
+    message_file: &Path,
+    source: CommitSource,
+) -> bool {
+    let policy = settings.hook.policy_for(source);
+    if policy == HookPolicy::Skip
+        || (policy == HookPolicy::Append && authors.coauthors().is_empty())
+    {
+        return false;
+    }
+
+    let content = fs::read_to_string(message_file)
+        .unwrap_or_else(|e| exit_with_error(Error::read(message_file, e)));
+    let message = CommitMessage::from(&content)
+        .with_comment_char(git::comment_char().unwrap_or_else(|| DEFAULT_COMMENT_CHAR.to_string()));
+    let updated = update_coauthor_trailers(message, &authors, policy, &settings.trailer);
+    if updated != content {
+        fs::write(message_file, updated)
+            .unwrap_or_else(|e| exit_with_error(Error::write(message_file, e)));
+    }
+    !authors.coauthors().is_empty()
+}
+
+pub fn handle_install_hook() {
+    let hooks_dir = git::hooks_dir().unwrap_or_else(|e| exit_with_error(e));
+    let binary = env::current_exe()
+        .unwrap_or_else(|e| exit_with_error(Error::io("Failed to locate binary", e)));
+    match install_hook(&hooks_dir, &binary) {
+        Ok(InstallOutcome::Installed) => println!("Installed hook in {}", hooks_dir.display()),
+        Ok(InstallOutcome::Updated) => println!("Updated hook in {}", hooks_dir.display()),
+        Ok(InstallOutcome::Chained) => println!(
+            "Installed hook in {}, the existing hook was moved to {} and will still run",
+            hooks_dir.display(),
+            CHAINED_HOOK_NAME
+        ),
+        Err(e) => exit_with_error(Error::io("Failed to install hook", e)),
+    }
+}
+
+pub fn handle_uninstall_hook() {
+    let hooks_dir = git::hooks_dir().unwrap_or_else(|e| exit_with_error(e));
+    match uninstall_hook(&hooks_dir) {
+        Ok(UninstallOutcome::Removed) => println!("Removed hook from {}", hooks_dir.display()),
+        Ok(UninstallOutcome::Restored) => println!(
+            "Removed hook from {} and restored the previous hook",
+            hooks_dir.display()
+        ),
+        Ok(UninstallOutcome::NotInstalled) => {
+            println!(
+                "No pair-commit-tool hook installed in {}",
+                hooks_dir.display()
+            )
+        }
+        Err(e) => exit_with_error(Error::io("Failed to uninstall hook", e)),
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum InstallOutcome {
+    Installed,
+    Updated,
+    Chained,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum UninstallOutcome {
+    Removed,
+    Restored,
+    NotInstalled,
+}
+
+fn install_hook(hooks_dir: &Path, binary: &Path) -> io::Result<InstallOutcome> {
+    fs::create_dir_all(hooks_dir)?;
+    let hook_path = hooks_dir.join(HOOK_NAME);
+    let chained_path = hooks_dir.join(CHAINED_HOOK_NAME);
+
+    let outcome = if !hook_path.exists() {
+        InstallOutcome::Installed
+    } else if is_installed_hook(&hook_path) {
+        InstallOutcome::Updated
+    } else if chained_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", chained_path.display()),
+        ));
+    } else {
+        fs::rename(&hook_path, &chained_path)?;
+        InstallOutcome::Chained
+    };
+
+    fs::write(&hook_path, hook_script(binary))?;
+    make_executable(&hook_path)?;
+    Ok(outcome)
+}
+
+fn uninstall_hook(hooks_dir: &Path) -> io::Result<UninstallOutcome> {
+    let hook_path = hooks_dir.join(HOOK_NAME);
+    let chained_path = hooks_dir.join(CHAINED_HOOK_NAME);
+    if !is_installed_hook(&hook_path) {
+        return Ok(UninstallOutcome::NotInstalled);
+    }
+
+    fs::remove_file(&hook_path)?;
+    if chained_path.exists() {
+        fs::rename(&chained_path, &hook_path)?;
+        Ok(UninstallOutcome::Restored)
+    } else {
+        Ok(UninstallOutcome::Removed)
+    }
+}
+
+fn is_installed_hook(hook_path: &Path) -> bool {
+    fs::read_to_string(hook_path)
+        .map(|content| content.contains(HOOK_MARKER))
+        .unwrap_or(false)
+}
+
+fn hook_script(binary: &Path) -> String {
+    format!(
+        "#!/bin/sh\n\
+         {marker}\n\
+         \n\
+         chained_hook=\"$(dirname \"$0\")/{chained}\"\n\
+         if test -x \"$chained_hook\"; then\n  \
+         \"$chained_hook\" \"$@\" || exit $?\n\
+         fi\n\
+         \n\
+         exec {binary} hook {hook} \"$@\"\n",
+        marker = HOOK_MARKER,
+        chained = CHAINED_HOOK_NAME,
+        binary = shell_quote(binary),
+        hook = HOOK_NAME
+    )
+}
+
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn update_coauthor_trailers<M: Into<CommitMessage>>(
+    message: M,
+    authors: &AuthorCollection,
+    policy: HookPolicy,
+    trailer: &TrailerSettings,
+) -> String {
+    let trailers = trailer.trailers(authors);
+    let mut message = message.into();
+    match policy {
+        HookPolicy::Append => message.add_trailers(&trailers),
+        HookPolicy::Replace => {
+            for key in trailer.keys_for(authors) {
+                message.remove_trailers(key);
+            }
+            message.add_trailers(&trailers)
+        }
+        HookPolicy::Skip => {}
+    }
+    message.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    use pair_commit_tool::models::author::role::Role;
+    use pair_commit_tool::models::author::ActiveState::ACTIVE;
+    use pair_commit_tool::models::author::Author;
+    use pair_commit_tool::models::commit_message::CommitSource;
+    use pair_commit_tool::models::settings::{Settings, TrailerSettings};
+
+    use crate::cli::hook::*;
+
+    fn hooks_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("pair-commit-tool-hooks-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn authors() -> AuthorCollection {
+        AuthorCollection::from(vec![
+            Author::with_active_state("Tester", "tester@test.com", ACTIVE),
+            Author::new("Inactive", "inactive@test.com"),
+        ])
+    }
+
+    #[test]
+    fn test_update_coauthor_trailers_append() {
+        assert_eq!(
+            "Subject\n\nCo-authored-by: Tester <tester@test.com>\n",
+            update_coauthor_trailers(
+                "Subject\n",
+                &authors(),
+                HookPolicy::Append,
+                &TrailerSettings::default()
+            )
+        );
+    }
+
+    #[test]
+    fn test_update_coauthor_trailers_append_twice() {
+        let once = update_coauthor_trailers(
+            "Subject\n",
+            &authors(),
+            HookPolicy::Append,
+            &TrailerSettings::default(),
+        );
+        assert_eq!(
+            once,
+            update_coauthor_trailers(
+                &once,
+                &authors(),
+                HookPolicy::Append,
+                &TrailerSettings::default()
+            )
+        );
+    }
+
+    #[test]
+    fn test_update_coauthor_trailers_replace() {
+        let message = "Subject\n\nCo-authored-by: Former <former@test.com>\n";
+        assert_eq!(
+            "Subject\n\nCo-authored-by: Tester <tester@test.com>\n",
+            update_coauthor_trailers(
+                message,
+                &authors(),
+                HookPolicy::Replace,
+                &TrailerSettings::default()
+            )
+        );
+    }
+
+    #[test]
+    fn test_update_coauthor_trailers_replace_without_active_authors() {
+        let message = "Subject\n\nCo-authored-by: Former <former@test.com>\n";
+        assert_eq!(
+            "Subject\n",
+            update_coauthor_trailers(
+                message,
+                &AuthorCollection::new(),
+                HookPolicy::Replace,
+                &TrailerSettings::default()
+            )
+        );
+    }
+
+    #[test]
+    fn test_update_coauthor_trailers_replace_role() {
+        let message = "Subject\n\nCo-authored-by: Tester <tester@test.com>\n";
+        let mut authors = authors();
+        authors.authors_mut()[0].set_role(Role::Reviewer);
+        assert_eq!(
+            "Subject\n\nReviewed-by: Tester <tester@test.com>\n",
+            update_coauthor_trailers(
+                message,
+                &authors,
+                HookPolicy::Replace,
+                &TrailerSettings::default()
+            )
+        );
+    }
+
+    #[test]
+    fn test_update_coauthor_trailers_replace_keeps_other_role_keys() {
+        let message = "Subject\n\nReviewed-by: Reviewer <reviewer@test.com>\n\
+                       Co-authored-by: Former <former@test.com>\n";
+        assert_eq!(
+            "Subject\n\nReviewed-by: Reviewer <reviewer@test.com>\n\
+             Co-authored-by: Tester <tester@test.com>\n",
+            update_coauthor_trailers(
+                message,
+                &authors(),
+                HookPolicy::Replace,
+                &TrailerSettings::default()
+            )
+        );
+    }
+
+    #[test]
+    fn test_update_coauthor_trailers_replace_custom_key() {
+        let message = "Subject\n\nPaired-with: Former <former@test.com>\n";
+        let trailer = TrailerSettings {
+            key: String::from("Paired-with"),
+            ..TrailerSettings::default()
+        };
+        assert_eq!(
+            "Subject\n\nPaired-with: Tester <tester@test.com>\n",
+            update_coauthor_trailers(message, &authors(), HookPolicy::Replace, &trailer)
+        );
+    }
+
+    #[test]
+    fn test_update_coauthor_trailers_skip() {
+        let message = "Merge branch 'feature'\n";
+        assert_eq!(
+            message,
+            update_coauthor_trailers(
+                message,
+                &authors(),
+                HookPolicy::Skip,
+                &TrailerSettings::default()
+            )
+        );
+    }
+
+    #[test]
+    fn test_prepare_commit_msg_credits_only_when_not_skipped() {
+        let dir = hooks_dir("message");
+        fs::create_dir_all(&dir).unwrap();
+        let message_file = dir.join("COMMIT_EDITMSG");
+        fs::write(&message_file, "Merge branch 'feature'\n").unwrap();
+        let settings = Settings::default();
+        assert!(!handle_prepare_commit_msg(
+            authors(),
+            &settings,
+            &message_file,
+            CommitSource::Merge
+        ));
+        assert_eq!(
+            "Merge branch 'feature'\n",
+            fs::read_to_string(&message_file).unwrap()
+        );
+        assert!(handle_prepare_commit_msg(
+            authors(),
+            &settings,
+            &message_file,
+            CommitSource::Message
+        ));
+        assert!(fs::read_to_string(&message_file)
+            .unwrap()
+            .contains("Co-authored-by: "));
+    }
+
+    #[test]
+    fn test_install_hook() {
+        let dir = hooks_dir("install");
+        let outcome = install_hook(&dir, Path::new("/bin/pair-commit-tool")).unwrap();
+        assert_eq!(InstallOutcome::Installed, outcome);
+        let script = fs::read_to_string(dir.join(HOOK_NAME)).unwrap();
+        assert!(script.contains("exec '/bin/pair-commit-tool' hook prepare-commit-msg \"$@\""));
+    }
+
+    #[test]
+    fn test_install_hook_twice_updates() {
+        let dir = hooks_dir("update");
+        install_hook(&dir, Path::new("/old/pair-commit-tool")).unwrap();
+        let outcome = install_hook(&dir, Path::new("/new/pair-commit-tool")).unwrap();
+        assert_eq!(InstallOutcome::Updated, outcome);
+        assert!(!dir.join(CHAINED_HOOK_NAME).exists());
+        let script = fs::read_to_string(dir.join(HOOK_NAME)).unwrap();
+        assert!(script.contains("/new/pair-commit-tool"));
+    }
+
+    #[test]
+    fn test_install_and_uninstall_chains_existing_hook() {
+        let dir = hooks_dir("chain");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(HOOK_NAME), "#!/bin/sh\necho existing\n").unwrap();
+
+        let outcome = install_hook(&dir, Path::new("/bin/pair-commit-tool")).unwrap();
+        assert_eq!(InstallOutcome::Chained, outcome);
+        assert!(dir.join(CHAINED_HOOK_NAME).exists());
+
+        assert_eq!(UninstallOutcome::Restored, uninstall_hook(&dir).unwrap());
+        assert_eq!(
+            "#!/bin/sh\necho existing\n",
+            fs::read_to_string(dir.join(HOOK_NAME)).unwrap()
+        );
+        assert!(!dir.join(CHAINED_HOOK_NAME).exists());
+    }
+
+    #[test]
+    fn test_uninstall_leaves_foreign_hook() {
+        let dir = hooks_dir("foreign");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(HOOK_NAME), "#!/bin/sh\n").unwrap();
+        assert_eq!(
+            UninstallOutcome::NotInstalled,
+            uninstall_hook(&dir).unwrap()
+        );
+        assert!(dir.join(HOOK_NAME).exists());
+    }
+
+    #[test]
+    fn test_uninstall_removes_hook() {
+        let dir = hooks_dir("remove");
+        install_hook(&dir, Path::new("/bin/pair-commit-tool")).unwrap();
+        assert_eq!(UninstallOutcome::Removed, uninstall_hook(&dir).unwrap());
+        assert!(!dir.join(HOOK_NAME).exists());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!("'/it'\\''s/bin'", shell_quote(Path::new("/it's/bin")));
+    }
+}
@@ -1,14 +1,55 @@
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
 
-use clap::{App, Arg, SubCommand};
+use chrono::{DateTime, Utc};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 
 use pair_commit_tool::models::author::author_collection::AuthorCollection;
-use pair_commit_tool::models::author::Author;
+use pair_commit_tool::models::author::role::Role;
+use pair_commit_tool::models::author::selection::{
+    SelectionError, SelectionTarget, SelectionToken,
+};
+use pair_commit_tool::models::author::ActiveState::{ACTIVE, INACTIVE};
+use pair_commit_tool::models::author::{
+    is_valid_alias, normalize_email, normalize_name, validate_email, validate_name, Author,
+};
+use pair_commit_tool::models::commit_message::CommitSource;
+use pair_commit_tool::models::session::{Session, SessionExpiry};
+use pair_commit_tool::models::settings::{OutputFormat, TrailerSettings};
 
-use crate::cli::user_input::get_user_input;
+use crate::cli::branch_pair::{
+    branch_pair_authors, branch_pair_sub_command, current_branch_pair, describe_branch_pair,
+    handle_branch_pair_sub_command, replace_branch_pair_references,
+};
+use crate::cli::hook::{
+    handle_install_hook, handle_prepare_commit_msg, handle_uninstall_hook, HookSubCommands,
+};
+use crate::cli::profile::{
+    describe_profile, handle_profile_sub_command, profile_sub_command, select_profile,
+};
+use crate::cli::session::{
+    format_time, handle_role_sub_command, handle_status_sub_command, load_current_session,
+    load_session, session_authors, update_session,
+};
+use crate::cli::settings::{config_sub_command, handle_config_sub_command, load_settings_layers};
+use crate::cli::store::Store;
+use crate::cli::team::{
+    handle_team_sub_command, load_teams, team_member_indexes_or_exit, team_sub_command,
+};
+use crate::cli::user_input::{get_user_input, is_interactive, read_values_from_stdin};
 use crate::config::Config;
-use crate::persistence;
+use crate::error::Error;
+use crate::git;
+use crate::persistence::{self, FileLock, BACKUP_COUNT};
 
+mod branch_pair;
+mod hook;
+mod profile;
+mod session;
+mod settings;
+mod store;
+mod team;
 mod user_input;
 
 enum CliSubCommands {
@@ -16,6 +57,25 @@ enum CliSubCommands {
     Add,
     Configure,
     Message,
+    Hook,
+    InstallHook,
+    UninstallHook,
+    Remove,
+    Archive,
+    Unarchive,
+    Edit,
+    With,
+    Solo,
+    Activate,
+    Deactivate,
+    Team,
+    Status,
+    BranchPair,
+    Config,
+    Profile,
+    Restore,
+    Dedupe,
+    Role,
 }
 
 impl CliSubCommands {
@@ -25,27 +85,162 @@ impl CliSubCommands {
             CliSubCommands::Add => "add",
             CliSubCommands::Configure => "configure",
             CliSubCommands::Message => "message",
+            CliSubCommands::Hook => "hook",
+            CliSubCommands::InstallHook => "install-hook",
+            CliSubCommands::UninstallHook => "uninstall-hook",
+            CliSubCommands::Remove => "remove",
+            CliSubCommands::Archive => "archive",
+            CliSubCommands::Unarchive => "unarchive",
+            CliSubCommands::Edit => "edit",
+            CliSubCommands::With => "with",
+            CliSubCommands::Solo => "solo",
+            CliSubCommands::Activate => "activate",
+            CliSubCommands::Deactivate => "deactivate",
+            CliSubCommands::Team => "team",
+            CliSubCommands::Status => "status",
+            CliSubCommands::BranchPair => "branch-pair",
+            CliSubCommands::Config => "config",
+            CliSubCommands::Profile => "profile",
+            CliSubCommands::Restore => "restore",
+            CliSubCommands::Dedupe => "dedupe",
+            CliSubCommands::Role => "role",
         }
     }
 }
 
 fn generate_new_config() -> Config {
-    match Config::new() {
-        Ok(config) => config,
-        _ => panic!("Save file path is missing from config"),
+    let config = Config::new().unwrap_or_else(|e| exit_with_error(e));
+    match config.migrate_legacy_home() {
+        Ok(moved) if !moved.is_empty() => {
+            eprintln!("Moved pair-commit-tool files to the XDG base directories:");
+            for (from, to) in moved {
+                eprintln!("  {} -> {}", from.display(), to.display());
+            }
+        }
+        Ok(_moved) => {}
+        Err(e) => exit_with_error(e),
     }
+    config
 }
 
-pub fn init() {
-    let config = generate_new_config();
-    let save_file_path = &config.save_file_path().unwrap();
+/// Prints the error and exits with the exit code of its kind
+fn exit_with_error<E: Into<Error>>(error: E) -> ! {
+    let error = error.into();
+    eprintln!("Error: {}", error);
+    process::exit(error.exit_code())
+}
 
+/// A file path of the tool, which is only missing when the home directory cannot be found
+fn file_path_or_exit(file_path: Option<PathBuf>) -> PathBuf {
+    file_path.unwrap_or_else(|| {
+        exit_with_error(Error::Config(String::from(
+            "Failed to find the files of the tool",
+        )))
+    })
+}
+
+/// Locks a file of the tool until the lock is dropped, so that runs of the tool that change it take
+/// turns
+///
+/// Locks are taken in the order authors, teams, co-authors bound to branches and session, so that
+/// two runs never wait for each other.
+fn lock_or_exit(file_path: &Path) -> FileLock {
+    persistence::lock(file_path).unwrap_or_else(|e| exit_with_error(e))
+}
+
+/// The value of an argument that clap already made sure is present
+fn required_value<'a>(matches: &'a ArgMatches, name: &str) -> &'a str {
+    matches
+        .value_of(name)
+        .unwrap_or_else(|| exit_with_error(format!("Missing value for {}", name)))
+}
+
+fn author_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("author")
+        .required(true)
+        .value_name("AUTHOR")
+        .help("Index, alias or email of the co-author")
+}
+
+fn selection_sub_command<'a, 'b>(name: &'a str, about: &'a str) -> App<'a, 'b> {
+    SubCommand::with_name(name)
+        .about(about)
+        .arg(
+            Arg::with_name("author")
+                .required_unless("from-stdin")
+                .multiple(true)
+                .value_name("AUTHOR")
+                .help("Index, range, alias or email of the co-authors"),
+        )
+        .arg(
+            Arg::with_name("from-stdin")
+                .long("from-stdin")
+                .required(false)
+                .takes_value(false)
+                .help("Also read co-authors from stdin, separated by commas or whitespace"),
+        )
+}
+
+fn alias_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("alias")
+        .long("alias")
+        .required(false)
+        .multiple(false)
+        .takes_value(true)
+        .value_name("ALIAS")
+        .help("Set a short unique name for selecting the co-author")
+}
+
+pub fn init() {
     let matches = App::new(crate_name!())
         .version(crate_version!())
         .author(crate_authors!())
+        .arg(
+            Arg::with_name("config")
+                .short("c")
+                .long("config")
+                .global(true)
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true)
+                .value_name("KEY=VALUE")
+                .help("Override a setting for this run"),
+        )
+        .arg(
+            Arg::with_name("profile")
+                .short("p")
+                .long("profile")
+                .global(true)
+                .takes_value(true)
+                .value_name("PROFILE")
+                .help("Use the co-authors and settings of a profile, or 'default'"),
+        )
+        .arg(
+            Arg::with_name("data-file")
+                .long("data-file")
+                .global(true)
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Use the co-authors in this file for this run"),
+        )
         .subcommand(
             SubCommand::with_name(CliSubCommands::List.get_string())
-                .about("Lists all co-authors with their metadata"),
+                .about("Lists all co-authors with their metadata")
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .required(false)
+                        .takes_value(false)
+                        .help("Include archived co-authors"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["text", "yaml"])
+                        .value_name("FORMAT")
+                        .help("Output format, overriding the output.format setting"),
+                ),
         )
         .subcommand(
             SubCommand::with_name(CliSubCommands::Add.get_string())
@@ -70,6 +265,7 @@ pub fn init() {
                         .value_name("EMAIL")
                         .help("Set new co-author email"),
                 )
+                .arg(alias_arg())
                 .arg(
                     Arg::with_name("active")
                         .short("a")
@@ -78,8 +274,107 @@ pub fn init() {
                         .multiple(false)
                         .takes_value(false)
                         .help("Set new co-author as active"),
+                )
+                .arg(
+                    Arg::with_name("merge")
+                        .long("merge")
+                        .takes_value(false)
+                        .help("Merge into the co-author that already has the email"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::Edit.get_string())
+                .about("Edit an existing co-author")
+                .arg(author_arg())
+                .arg(
+                    Arg::with_name("name")
+                        .short("n")
+                        .long("name")
+                        .takes_value(true)
+                        .value_name("NAME")
+                        .help("Set co-author name"),
+                )
+                .arg(
+                    Arg::with_name("email")
+                        .short("e")
+                        .long("email")
+                        .takes_value(true)
+                        .value_name("EMAIL")
+                        .help("Set co-author email, keeping the old one as a secondary email"),
+                )
+                .arg(alias_arg().empty_values(true))
+                .arg(
+                    Arg::with_name("active")
+                        .short("a")
+                        .long("active")
+                        .takes_value(false)
+                        .conflicts_with("inactive")
+                        .help("Set co-author as active"),
+                )
+                .arg(
+                    Arg::with_name("inactive")
+                        .short("i")
+                        .long("inactive")
+                        .takes_value(false)
+                        .help("Set co-author as inactive"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::With.get_string())
+                .about("Make exactly the given co-authors active")
+                .arg(author_arg().multiple(true).required_unless("team"))
+                .arg(
+                    Arg::with_name("team")
+                        .short("t")
+                        .long("team")
+                        .multiple(true)
+                        .number_of_values(1)
+                        .takes_value(true)
+                        .value_name("TEAM")
+                        .help("Make the members of a team active"),
+                ),
+        )
+        .subcommand(team_sub_command(CliSubCommands::Team.get_string()))
+        .subcommand(branch_pair_sub_command(
+            CliSubCommands::BranchPair.get_string(),
+        ))
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::Solo.get_string())
+                .about("Make all co-authors inactive"),
+        )
+        .subcommand(selection_sub_command(
+            CliSubCommands::Activate.get_string(),
+            "Make co-authors active, leaving the others as they are",
+        ))
+        .subcommand(selection_sub_command(
+            CliSubCommands::Deactivate.get_string(),
+            "Make co-authors inactive, leaving the others as they are",
+        ))
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::Remove.get_string())
+                .about("Remove a co-author")
+                .arg(author_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::Dedupe.get_string())
+                .about("Merge co-authors that share an email into the first of them")
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .takes_value(false)
+                        .help("Only show which co-authors would be merged"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::Archive.get_string())
+                .about("Archive a co-author, hiding them without deleting their data")
+                .arg(author_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::Unarchive.get_string())
+                .about("Restore an archived co-author")
+                .arg(author_arg()),
+        )
         .subcommand(
             SubCommand::with_name(CliSubCommands::Configure.get_string())
                 .about("Configure which co-authors are active"),
@@ -88,60 +383,699 @@ pub fn init() {
             SubCommand::with_name(CliSubCommands::Message.get_string())
                 .about("Get a co-authors message to append to a git commit"),
         )
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::Status.get_string())
+                .about("Show the active co-authors and when their session expires"),
+        )
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::Role.get_string())
+                .about("Give active co-authors a role, which decides the key of their trailer")
+                .arg(
+                    Arg::with_name("role")
+                        .required(true)
+                        .value_name("ROLE")
+                        .possible_values(&["driver", "navigator", "reviewer"])
+                        .help("Role for the rest of the session"),
+                )
+                .arg(author_arg().multiple(true)),
+        )
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::Hook.get_string())
+                .about("Run as a git hook")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name(HookSubCommands::PrepareCommitMsg.get_string())
+                        .about("Add active co-author trailers to a commit message file")
+                        .arg(
+                            Arg::with_name("file")
+                                .required(true)
+                                .value_name("FILE")
+                                .help("Commit message file"),
+                        )
+                        .arg(
+                            Arg::with_name("source")
+                                .required(false)
+                                .value_name("SOURCE")
+                                .help("Source of the commit message"),
+                        )
+                        .arg(
+                            Arg::with_name("sha")
+                                .required(false)
+                                .value_name("SHA")
+                                .help("Commit SHA-1 when amending or reusing a commit"),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::InstallHook.get_string())
+                .about("Install the prepare-commit-msg hook in the current git repository"),
+        )
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::UninstallHook.get_string())
+                .about("Remove the prepare-commit-msg hook from the current git repository"),
+        )
+        .subcommand(config_sub_command(CliSubCommands::Config.get_string()))
+        .subcommand(profile_sub_command(CliSubCommands::Profile.get_string()))
+        .subcommand(
+            SubCommand::with_name(CliSubCommands::Restore.get_string())
+                .about("Restore the co-authors from one of the backups kept when they change")
+                .arg(
+                    Arg::with_name("backup")
+                        .value_name("BACKUP")
+                        .default_value("1")
+                        .help("Number of the backup, where 1 is the newest"),
+                )
+                .arg(
+                    Arg::with_name("list")
+                        .long("list")
+                        .takes_value(false)
+                        .help("List the backups instead of restoring one"),
+                ),
+        )
         .get_matches();
 
-    if let Some(_list_matches) = matches.subcommand_matches(CliSubCommands::List.get_string()) {
-        let authors =
-            persistence::load(PathBuf::from(save_file_path)).expect("Failed to load existing data");
-        handle_list_sub_command(authors);
-    } else if let Some(add_matches) = matches.subcommand_matches(CliSubCommands::Add.get_string()) {
-        let authors =
-            persistence::load(PathBuf::from(save_file_path)).expect("Failed to load existing data");
-        let author = Author::with_active_state(
-            add_matches
-                .value_of("name")
-                .expect("Name value not found")
-                .to_string(),
-            add_matches
-                .value_of("email")
-                .expect("Email value not found")
-                .to_string(),
-            add_matches.is_present("active").into(),
+    let config = generate_new_config();
+    let profile = select_profile(&config, matches.value_of("profile"));
+    let config = config
+        .with_profile(profile.as_ref().map(|(name, _source)| name.clone()))
+        .with_data_file(matches.value_of("data-file").map(PathBuf::from));
+    let settings_layers = load_settings_layers(&config, &setting_overrides(&matches));
+    let settings = settings_layers
+        .settings()
+        .unwrap_or_else(|e| exit_with_error(Error::Config(format!("Invalid settings: {}", e))));
+    let store = Store::new(&config, settings.state.scope);
+
+    if let Some(list_matches) = matches.subcommand_matches(CliSubCommands::List.get_string()) {
+        let authors = store.load_authors();
+        handle_list_sub_command(
+            authors,
+            list_matches.is_present("all"),
+            settings.output.format,
         );
-        handle_add_sub_command(authors, author, save_file_path);
+    } else if let Some(add_matches) = matches.subcommand_matches(CliSubCommands::Add.get_string()) {
+        let _lock = store.lock_authors();
+        let authors = store.load_authors();
+        let mut author = Author::try_new(
+            required_value(add_matches, "name"),
+            required_value(add_matches, "email"),
+        )
+        .unwrap_or_else(|e| exit_with_error(e));
+        author.set_active(add_matches.is_present("active").into());
+        let alias = add_matches.value_of("alias");
+        let merge = add_matches.is_present("merge");
+        handle_add_sub_command(authors, author, alias, merge, &store);
+    } else if let Some(edit_matches) = matches.subcommand_matches(CliSubCommands::Edit.get_string())
+    {
+        let _lock = store.lock_authors();
+        let authors = store.load_authors();
+        handle_edit_sub_command(authors, edit_matches, &store);
+    } else if let Some(with_matches) = matches.subcommand_matches(CliSubCommands::With.get_string())
+    {
+        let _lock = store.lock_authors();
+        let authors = store.load_authors();
+        let teams = load_teams(&authors, &file_path_or_exit(config.teams_file_path()));
+        let selectors: Vec<&str> = with_matches
+            .values_of("author")
+            .map(|values| values.collect())
+            .unwrap_or_default();
+        let mut indexes = find_author_indexes_or_exit(&authors, &selectors);
+        for team in with_matches.values_of("team").into_iter().flatten() {
+            indexes.extend(team_member_indexes_or_exit(&authors, &teams, team));
+        }
+        handle_with_sub_command(authors, &indexes, &settings.trailer, &store);
+    } else if let Some(_solo_matches) =
+        matches.subcommand_matches(CliSubCommands::Solo.get_string())
+    {
+        let _lock = store.lock_authors();
+        let authors = store.load_authors();
+        handle_solo_sub_command(authors, &store);
+    } else if let Some(team_matches) = matches.subcommand_matches(CliSubCommands::Team.get_string())
+    {
+        let authors = store.load_authors();
+        let teams_file_path = file_path_or_exit(config.teams_file_path());
+        let _teams_lock = lock_or_exit(&teams_file_path);
+        let teams = load_teams(&authors, &teams_file_path);
+        handle_team_sub_command(&authors, teams, team_matches, &teams_file_path);
+    } else if let Some(branch_pair_matches) =
+        matches.subcommand_matches(CliSubCommands::BranchPair.get_string())
+    {
+        let authors = store.load_authors();
+        handle_branch_pair_sub_command(&authors, branch_pair_matches, &store);
+    } else if let Some(activate_matches) =
+        matches.subcommand_matches(CliSubCommands::Activate.get_string())
+    {
+        let selectors = selectors_from_matches(activate_matches);
+        let _lock = store.lock_authors();
+        let authors = store.load_authors();
+        handle_toggle_sub_command(authors, &selectors, true, &store);
+    } else if let Some(deactivate_matches) =
+        matches.subcommand_matches(CliSubCommands::Deactivate.get_string())
+    {
+        let selectors = selectors_from_matches(deactivate_matches);
+        let _lock = store.lock_authors();
+        let authors = store.load_authors();
+        handle_toggle_sub_command(authors, &selectors, false, &store);
+    } else if let Some(remove_matches) =
+        matches.subcommand_matches(CliSubCommands::Remove.get_string())
+    {
+        let _lock = store.lock_authors();
+        let authors = store.load_authors();
+        let selector = required_value(remove_matches, "author");
+        handle_remove_sub_command(authors, selector, &store);
+    } else if let Some(dedupe_matches) =
+        matches.subcommand_matches(CliSubCommands::Dedupe.get_string())
+    {
+        let _lock = store.lock_authors();
+        let authors = store.load_authors();
+        let teams_file_path = file_path_or_exit(config.teams_file_path());
+        let dry_run = dedupe_matches.is_present("dry-run");
+        handle_dedupe_sub_command(authors, &teams_file_path, dry_run, &store);
+    } else if let Some(archive_matches) =
+        matches.subcommand_matches(CliSubCommands::Archive.get_string())
+    {
+        let _lock = store.lock_authors();
+        let authors = store.load_authors();
+        let selector = required_value(archive_matches, "author");
+        handle_archive_sub_command(authors, selector, true, &store);
+    } else if let Some(unarchive_matches) =
+        matches.subcommand_matches(CliSubCommands::Unarchive.get_string())
+    {
+        let _lock = store.lock_authors();
+        let authors = store.load_authors();
+        let selector = required_value(unarchive_matches, "author");
+        handle_archive_sub_command(authors, selector, false, &store);
     } else if let Some(_message_matches) =
         matches.subcommand_matches(CliSubCommands::Message.get_string())
     {
-        let authors =
-            persistence::load(PathBuf::from(save_file_path)).expect("Failed to load existing data");
-        handle_message_sub_command(authors);
+        let authors = store.load_authors();
+        let mut authors = match current_branch_pair(&store) {
+            Some((_branch, pair)) => branch_pair_authors(authors, &pair),
+            None => {
+                let session = load_current_session(&authors, &store.session_file_path());
+                session_authors(authors, &session, settings.session.expiry)
+            }
+        };
+        authors.set_committer(git::author_email());
+        handle_message_sub_command(authors, &settings.trailer);
+    } else if let Some(role_matches) = matches.subcommand_matches(CliSubCommands::Role.get_string())
+    {
+        let authors = store.load_authors();
+        let role = required_value(role_matches, "role")
+            .parse::<Role>()
+            .unwrap_or_else(|e| exit_with_error(e));
+        let selectors: Vec<&str> = role_matches
+            .values_of("author")
+            .map(|values| values.collect())
+            .unwrap_or_default();
+        let indexes = find_author_indexes_or_exit(&authors, &selectors);
+        handle_role_sub_command(&authors, &indexes, role, &store.session_file_path());
+    } else if let Some(_status_matches) =
+        matches.subcommand_matches(CliSubCommands::Status.get_string())
+    {
+        let mut authors = store.load_authors();
+        authors.set_committer(git::author_email());
+        if profile.is_some() {
+            println!("{}", describe_profile(&profile));
+        }
+        match current_branch_pair(&store) {
+            Some((branch, pair)) => handle_status_sub_command(
+                &branch_pair_authors(authors, &pair),
+                &Session::default(),
+                SessionExpiry::Never,
+                &describe_branch_pair(&branch, &pair),
+            ),
+            None => handle_status_sub_command(
+                &authors,
+                &load_current_session(&authors, &store.session_file_path()),
+                settings.session.expiry,
+                &store.describe_scope(),
+            ),
+        }
+    } else if let Some(config_matches) =
+        matches.subcommand_matches(CliSubCommands::Config.get_string())
+    {
+        handle_config_sub_command(&settings_layers, config_matches, &config);
+    } else if let Some(profile_matches) =
+        matches.subcommand_matches(CliSubCommands::Profile.get_string())
+    {
+        handle_profile_sub_command(&config, profile_matches, &profile);
+    } else if let Some(restore_matches) =
+        matches.subcommand_matches(CliSubCommands::Restore.get_string())
+    {
+        handle_restore_sub_command(&file_path_or_exit(config.save_file_path()), restore_matches);
     } else if let Some(_configure_matches) =
         matches.subcommand_matches(CliSubCommands::Configure.get_string())
     {
-        let authors = persistence::load(PathBuf::from(save_file_path)).expect("failed");
-        handle_configure_sub_command(authors, save_file_path);
+        handle_configure_sub_command(&store);
+    } else if let Some(hook_matches) = matches.subcommand_matches(CliSubCommands::Hook.get_string())
+    {
+        if let Some(prepare_matches) =
+            hook_matches.subcommand_matches(HookSubCommands::PrepareCommitMsg.get_string())
+        {
+            let authors = store.load_authors();
+            let branch_pair = current_branch_pair(&store);
+            let _session_lock = lock_or_exit(&store.session_file_path());
+            let mut session = load_current_session(&authors, &store.session_file_path());
+            let mut authors = match &branch_pair {
+                Some((_branch, pair)) => branch_pair_authors(authors, pair),
+                None => session_authors(authors, &session, settings.session.expiry),
+            };
+            authors.set_committer(git::author_email());
+            let message_file = PathBuf::from(required_value(prepare_matches, "file"));
+            let source = CommitSource::from(prepare_matches.value_of("source").unwrap_or(""))
+                .reusing_head(prepare_matches.value_of("sha").is_some_and(git::is_head));
+            let credited = handle_prepare_commit_msg(authors, &settings, &message_file, source);
+            // Bound co-authors have no session, so commits on the branch leave it as it is
+            if credited && branch_pair.is_none() {
+                session.record_commit(Utc::now());
+                persistence::save_session(store.session_file_path(), &session)
+                    .unwrap_or_else(|e| exit_with_error(e));
+            }
+        }
+    } else if let Some(_install_matches) =
+        matches.subcommand_matches(CliSubCommands::InstallHook.get_string())
+    {
+        handle_install_hook();
+    } else if let Some(_uninstall_matches) =
+        matches.subcommand_matches(CliSubCommands::UninstallHook.get_string())
+    {
+        handle_uninstall_hook();
+    }
+}
+
+/// Settings given on the command line as `KEY=VALUE`, including those set by specific flags
+fn setting_overrides(matches: &ArgMatches) -> Vec<String> {
+    let mut overrides: Vec<String> = matches
+        .values_of("config")
+        .map(|values| values.map(String::from).collect())
+        .unwrap_or_default();
+    if let Some(format) = matches
+        .subcommand_matches(CliSubCommands::List.get_string())
+        .and_then(|list_matches| list_matches.value_of("format"))
+    {
+        overrides.push(format!("output.format={}", format));
+    }
+    overrides
+}
+
+fn handle_list_sub_command(
+    author_col: AuthorCollection,
+    include_archived: bool,
+    format: OutputFormat,
+) {
+    match format {
+        OutputFormat::Text if include_archived => {
+            println!("{}", author_col.all_authors_with_indexes())
+        }
+        OutputFormat::Text => println!("{}", author_col.authors_with_indexes()),
+        OutputFormat::Yaml => {
+            let authors: Vec<&Author> = author_col
+                .authors()
+                .iter()
+                .filter(|author| include_archived || !author.archived())
+                .collect();
+            match serde_yaml::to_string(&authors) {
+                Ok(yaml) => println!("{}", yaml.trim_end()),
+                Err(e) => exit_with_error(format!("Failed to format co-authors: {}", e)),
+            }
+        }
+    }
+}
+
+fn find_author_index_or_exit(authors: &AuthorCollection, selector: &str) -> usize {
+    authors
+        .find_author_index(selector)
+        .unwrap_or_else(|| exit_with_error(format!("No co-author matches '{}'", selector)))
+}
+
+fn handle_edit_sub_command(
+    mut authors: AuthorCollection,
+    edit_matches: &ArgMatches,
+    store: &Store,
+) {
+    let selector = required_value(edit_matches, "author");
+    if !["name", "email", "alias", "active", "inactive"]
+        .iter()
+        .any(|arg| edit_matches.is_present(arg))
+    {
+        exit_with_error("Nothing to edit, use --name, --email, --alias, --active or --inactive");
+    }
+    let index = find_author_index_or_exit(&authors, selector);
+    if let Some(alias) = edit_matches.value_of("alias").filter(|a| !a.is_empty()) {
+        validate_alias_or_exit(&authors, alias, Some(index));
+    }
+    if let Some(name) = edit_matches.value_of("name") {
+        validate_name_or_exit(&normalize_name(name));
+    }
+    if let Some(email) = edit_matches.value_of("email") {
+        let email = &normalize_email(email);
+        validate_email_or_exit(email);
+        let used_by = authors
+            .authors()
+            .iter()
+            .position(|author| author.has_email(email))
+            .filter(|&other| other != index);
+        if let Some(other) = used_by {
+            exit_with_error(format!(
+                "{} is already an email of co-author {}, {}",
+                email,
+                other,
+                authors.authors()[other]
+            ));
+        }
+    }
+    let author = &mut authors.authors_mut()[index];
+    if let Some(name) = edit_matches.value_of("name") {
+        author.set_name(name);
+    }
+    if let Some(email) = edit_matches.value_of("email") {
+        author.set_email(email);
+    }
+    if let Some(alias) = edit_matches.value_of("alias") {
+        author.set_alias(Some(alias).filter(|a| !a.is_empty()));
+    }
+    if edit_matches.is_present("active") {
+        author.set_active(ACTIVE);
+    } else if edit_matches.is_present("inactive") {
+        author.set_active(INACTIVE);
+    }
+    println!("Updated {}", author);
+    store.save_authors(&mut authors);
+    update_session(
+        &authors,
+        edit_matches.is_present("active"),
+        &store.session_file_path(),
+    );
+}
+
+fn handle_remove_sub_command(mut authors: AuthorCollection, selector: &str, store: &Store) {
+    let index = find_author_index_or_exit(&authors, selector);
+    let removed = authors.remove_author(index);
+    store.save_authors(&mut authors);
+    update_session(&authors, false, &store.session_file_path());
+    println!("Removed {}", removed);
+}
+
+fn handle_archive_sub_command(
+    mut authors: AuthorCollection,
+    selector: &str,
+    archive: bool,
+    store: &Store,
+) {
+    let index = find_author_index_or_exit(&authors, selector);
+    let author = &mut authors.authors_mut()[index];
+    if archive {
+        author.archive();
+        println!("Archived {}", author);
+    } else {
+        author.unarchive();
+        println!("Restored {}", author);
+    }
+    store.save_authors(&mut authors);
+    update_session(&authors, false, &store.session_file_path());
+}
+
+fn validate_name_or_exit(name: &str) {
+    validate_name(name).unwrap_or_else(|e| exit_with_error(e));
+}
+
+fn validate_email_or_exit(email: &str) {
+    validate_email(email).unwrap_or_else(|e| exit_with_error(e));
+}
+
+fn handle_dedupe_sub_command(
+    mut authors: AuthorCollection,
+    teams_file_path: &Path,
+    dry_run: bool,
+    store: &Store,
+) {
+    if dry_run {
+        println!("{}", describe_duplicates(&authors));
+        return;
+    }
+    let _teams_lock = lock_or_exit(teams_file_path);
+    let mut teams = load_teams(&authors, teams_file_path);
+    let merged = authors.dedupe();
+    if merged.is_empty() {
+        println!("No co-authors share an email");
+        return;
+    }
+    let mut replaced = Vec::new();
+    for (kept, duplicate) in &merged {
+        teams.replace_reference(duplicate.reference(), kept);
+        replaced.push((duplicate.reference().clone(), kept.clone()));
+        if let Some(index) = authors.find_author_index_by_id(kept) {
+            println!("Merged {} into {}", duplicate, authors.authors()[index]);
+        }
+    }
+    persistence::save_teams(teams_file_path.to_path_buf(), &teams)
+        .unwrap_or_else(|e| exit_with_error(e));
+    replace_branch_pair_references(store, &replaced);
+    store.save_merged_authors(&mut authors, &replaced);
+    update_session(&authors, false, &store.session_file_path());
+}
+
+fn describe_duplicates(authors: &AuthorCollection) -> String {
+    let groups = authors.duplicate_groups();
+    if groups.is_empty() {
+        return String::from("No co-authors share an email");
+    }
+    groups
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .map(|&index| format!("{}: {}", index, authors.authors()[index]))
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n---\n")
+}
+
+fn validate_alias_or_exit(authors: &AuthorCollection, alias: &str, except: Option<usize>) {
+    if !is_valid_alias(alias) {
+        exit_with_error(format!(
+            "Invalid alias '{}', aliases cannot be numbers, start with '+' or '-', or contain \
+             whitespace, ',' or '@'",
+            alias
+        ));
+    }
+    if authors.alias_in_use(alias, except) {
+        exit_with_error(format!("Alias '{}' is already in use", alias));
+    }
+}
+
+fn handle_add_sub_command(
+    mut authors: AuthorCollection,
+    mut new_author: Author,
+    alias: Option<&str>,
+    merge: bool,
+    store: &Store,
+) {
+    let duplicate = authors.find_duplicate(&new_author);
+    if let Some(alias) = alias {
+        validate_alias_or_exit(&authors, alias, duplicate.filter(|_index| merge));
+        new_author.set_alias(Some(alias));
+    }
+    let activated = new_author.active();
+    match duplicate {
+        None => authors.add_author(new_author),
+        Some(index) if merge => {
+            let existing = &mut authors.authors_mut()[index];
+            existing.merge(&new_author);
+            if alias.is_some() {
+                existing.set_alias(alias);
+            }
+            println!("Merged {} into {}", new_author, existing);
+        }
+        Some(index) => exit_with_error(format!(
+            "{} has an email of co-author {}, {}, use --merge to merge them",
+            new_author,
+            index,
+            authors.authors()[index]
+        )),
+    }
+    store.save_authors(&mut authors);
+    update_session(&authors, activated, &store.session_file_path());
+}
+
+fn find_author_indexes_or_exit(authors: &AuthorCollection, selectors: &[&str]) -> Vec<usize> {
+    let unknown: Vec<&str> = selectors
+        .iter()
+        .filter(|selector| authors.find_author_index(selector).is_none())
+        .copied()
+        .collect();
+    if !unknown.is_empty() {
+        exit_with_error(format!("No co-author matches '{}'", unknown.join("', '")));
+    }
+    selectors
+        .iter()
+        .filter_map(|selector| authors.find_author_index(selector))
+        .collect()
+}
+
+fn handle_with_sub_command(
+    mut authors: AuthorCollection,
+    indexes: &[usize],
+    trailer: &TrailerSettings,
+    store: &Store,
+) {
+    authors.set_active_authors_by_indexes(indexes);
+    store.save_authors(&mut authors);
+    update_session(&authors, true, &store.session_file_path());
+    authors.set_roles_by_ids(load_session(&store.session_file_path()).roles());
+    authors.set_committer(git::author_email());
+    println!("{}", trailer.trailers(&authors).join("\n"));
+}
+
+fn selectors_from_matches(matches: &ArgMatches) -> Vec<String> {
+    let mut selectors: Vec<String> = matches
+        .values_of("author")
+        .map(|values| values.map(String::from).collect())
+        .unwrap_or_default();
+    if matches.is_present("from-stdin") {
+        selectors.extend(read_values_from_stdin());
     }
+    selectors
 }
 
-fn handle_list_sub_command(author_col: AuthorCollection) {
-    println!("{}", author_col.authors_with_indexes());
+fn handle_toggle_sub_command(
+    mut authors: AuthorCollection,
+    selectors: &[String],
+    activate: bool,
+    store: &Store,
+) {
+    let mut errors: Vec<SelectionError> = Vec::new();
+    let mut tokens: Vec<SelectionToken> = Vec::new();
+    for selector in selectors {
+        match selector.parse::<SelectionTarget>() {
+            Ok(target) if activate => tokens.push(SelectionToken::Add(target)),
+            Ok(target) => tokens.push(SelectionToken::Remove(target)),
+            Err(e) => errors.push(e),
+        }
+    }
+    let selected = match authors.resolve_selection(&tokens) {
+        Ok(selected) => selected,
+        Err(resolve_errors) => {
+            errors.extend(resolve_errors);
+            Vec::new()
+        }
+    };
+    if !errors.is_empty() {
+        exit_with_error(
+            errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+        );
+    }
+
+    authors.set_active_authors_by_indexes(&selected);
+    store.save_authors(&mut authors);
+    update_session(&authors, activate, &store.session_file_path());
 }
 
-fn handle_add_sub_command(mut authors: AuthorCollection, new_author: Author, file_path: &PathBuf) {
-    authors.add_author(new_author);
-    persistence::save(PathBuf::from(file_path), &authors);
+fn handle_solo_sub_command(mut authors: AuthorCollection, store: &Store) {
+    authors.deactivate_all();
+    store.save_authors(&mut authors);
+    update_session(&authors, false, &store.session_file_path());
 }
 
-fn handle_message_sub_command(authors: AuthorCollection) {
-    println!("{}", authors.join_all_active_coauthor_strings());
+fn handle_message_sub_command(authors: AuthorCollection, trailer: &TrailerSettings) {
+    println!("{}", trailer.trailers(&authors).join("\n"));
 }
 
-fn handle_configure_sub_command(mut authors: AuthorCollection, file_path: &PathBuf) {
+fn handle_restore_sub_command(data_file: &Path, matches: &ArgMatches) {
+    if matches.is_present("list") {
+        println!("{}", describe_backups(data_file));
+        return;
+    }
+    let number = required_value(matches, "backup")
+        .parse::<usize>()
+        .ok()
+        .filter(|number| (1..=BACKUP_COUNT).contains(number))
+        .unwrap_or_else(|| {
+            exit_with_error(format!(
+                "Invalid backup, use a number from 1 to {}",
+                BACKUP_COUNT
+            ))
+        });
+    let _lock = lock_or_exit(data_file);
+    let authors = persistence::restore(data_file.to_path_buf(), number)
+        .unwrap_or_else(|e| exit_with_error(e));
+    println!(
+        "Restored {} co-authors from backup {}, the replaced co-authors are now backup 1",
+        authors.authors().len(),
+        number
+    );
+}
+
+fn describe_backups(data_file: &Path) -> String {
+    let backups = persistence::backups(data_file);
+    if backups.is_empty() {
+        return format!("No backups of {}", data_file.display());
+    }
+    backups
+        .iter()
+        .map(|(number, path)| {
+            let modified = fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .map(|time| format_time(DateTime::<Utc>::from(time)))
+                .unwrap_or_default();
+            format!("{}\t{}\t{}", number, modified, path.display())
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Asks which co-authors to activate, then takes the lock and applies the answer to the authors
+/// as they are by then, so that other runs of the tool are not held up while waiting for input
+fn handle_configure_sub_command(store: &Store) {
+    if !is_interactive() {
+        exit_with_error(
+            "configure needs an interactive terminal, use activate, deactivate or with instead",
+        );
+    }
+    let authors = store.load_authors();
     let output: String = authors.authors_with_indexes();
     println!("{}", output);
-    let indexes = get_user_input::<String, i32>(String::from(
-        "Enter the indexes of the authors to be active (comma separated)",
-    ));
+    let tokens = loop {
+        let parts = get_user_input::<String, String>(String::from(
+            "Enter the authors to be active as indexes, ranges, aliases or emails, \
+             'all' or 'none', or +/- to add or remove (comma separated)",
+        ))
+        .unwrap_or_default();
+        let (tokens, mut errors) = SelectionToken::parse_all(&parts);
+        match authors.resolve_selection(&tokens) {
+            Ok(_indexes) if errors.is_empty() => break tokens,
+            Ok(_indexes) => {}
+            Err(resolve_errors) => errors.extend(resolve_errors),
+        }
+        for error in errors {
+            eprintln!("Error: {}", error);
+        }
+    };
+
+    let _lock = store.lock_authors();
+    let mut authors = store.load_authors();
+    let indexes = authors.resolve_selection(&tokens).unwrap_or_else(|errors| {
+        exit_with_error(format!(
+            "The co-authors changed while waiting for input: {}",
+            errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ))
+    });
+    let active_ids = authors.active_ids();
     authors.set_active_authors_by_indexes(&indexes);
-    persistence::save(PathBuf::from(file_path), &authors);
+    // An unchanged selection, such as an empty answer, keeps the session running as it is
+    if authors.active_ids() == active_ids {
+        return;
+    }
+    store.save_authors(&mut authors);
+    update_session(&authors, true, &store.session_file_path());
 }
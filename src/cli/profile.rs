@@ -0,0 +1,264 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+use pair_commit_tool::models::profile::{is_valid_profile_name, ProfileCollection};
+
+use crate::cli::{exit_with_error, file_path_or_exit, required_value};
+use crate::config::Config;
+use crate::persistence;
+
+const PROFILE_VARIABLE: &str = "PAIR_COMMIT_PROFILE";
+/// Selects the default files, even in a directory that has a profile
+const DEFAULT_PROFILE: &str = "default";
+
+pub enum ProfileSubCommands {
+    List,
+    AddDirectory,
+    RemoveDirectory,
+}
+
+impl ProfileSubCommands {
+    pub fn get_string(&self) -> &str {
+        match self {
+            ProfileSubCommands::List => "list",
+            ProfileSubCommands::AddDirectory => "add-directory",
+            ProfileSubCommands::RemoveDirectory => "remove-directory",
+        }
+    }
+}
+
+/// Why a profile is used
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ProfileSource {
+    Flag,
+    Environment,
+    Directory(PathBuf),
+}
+
+fn profile_name_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("name")
+        .required(true)
+        .value_name("PROFILE")
+        .help("Name of the profile")
+}
+
+fn directory_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("directory")
+        .required(true)
+        .value_name("DIRECTORY")
+        .help("Directory the profile is used in, including its subdirectories")
+}
+
+pub fn profile_sub_command<'a, 'b>(name: &'a str) -> App<'a, 'b> {
+    SubCommand::with_name(name)
+        .about("Manage profiles, each with their own co-authors and settings")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name(ProfileSubCommands::List.get_string())
+                .about("List profiles with the directories they are used in"),
+        )
+        .subcommand(
+            SubCommand::with_name(ProfileSubCommands::AddDirectory.get_string())
+                .about("Use a profile in a directory")
+                .arg(profile_name_arg())
+                .arg(directory_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name(ProfileSubCommands::RemoveDirectory.get_string())
+                .about("Stop using a profile in a directory")
+                .arg(profile_name_arg())
+                .arg(directory_arg()),
+        )
+}
+
+/// The profile given with `--profile`, `PAIR_COMMIT_PROFILE` or a directory rule, in that order,
+/// or `None` for the default files
+pub fn select_profile(config: &Config, flag: Option<&str>) -> Option<(String, ProfileSource)> {
+    let (name, source) = match flag {
+        Some(name) => (name.to_string(), ProfileSource::Flag),
+        None => match env::var(PROFILE_VARIABLE)
+            .ok()
+            .filter(|name| !name.is_empty())
+        {
+            Some(name) => (name, ProfileSource::Environment),
+            None => {
+                let cwd = env::current_dir().ok()?;
+                let home = dirs::home_dir().unwrap_or_default();
+                let profiles = load_profiles(config);
+                let (profile, dir) = profiles.find_for_directory(&cwd, &home)?;
+                (profile.name().clone(), ProfileSource::Directory(dir))
+            }
+        },
+    };
+    if name == DEFAULT_PROFILE {
+        return None;
+    }
+    if !is_valid_profile_name(&name) {
+        exit_with_error(format!(
+            "Invalid profile '{}', profile names cannot be empty, start with '.' or contain \
+             whitespace or path separators",
+            name
+        ));
+    }
+    Some((name, source))
+}
+
+pub fn describe_profile(profile: &Option<(String, ProfileSource)>) -> String {
+    match profile {
+        None => String::from("Using the default profile"),
+        Some((name, ProfileSource::Flag)) => format!("Using profile {} (--profile)", name),
+        Some((name, ProfileSource::Environment)) => {
+            format!("Using profile {} ({})", name, PROFILE_VARIABLE)
+        }
+        Some((name, ProfileSource::Directory(dir))) => {
+            format!("Using profile {} (directory {})", name, dir.display())
+        }
+    }
+}
+
+pub fn handle_profile_sub_command(
+    config: &Config,
+    matches: &ArgMatches,
+    current: &Option<(String, ProfileSource)>,
+) {
+    let file_path = file_path_or_exit(config.profiles_file_path());
+    let mut profiles = load_profiles(config);
+    if let Some(add_matches) =
+        matches.subcommand_matches(ProfileSubCommands::AddDirectory.get_string())
+    {
+        let (name, directory) = profile_and_directory(add_matches);
+        profiles.find_or_add_profile(&name).add_directory(directory);
+    } else if let Some(remove_matches) =
+        matches.subcommand_matches(ProfileSubCommands::RemoveDirectory.get_string())
+    {
+        let (name, directory) = profile_and_directory(remove_matches);
+        let removed = profiles
+            .find_or_add_profile(&name)
+            .remove_directory(&directory);
+        profiles.remove_empty();
+        if !removed {
+            exit_with_error(format!("Profile '{}' is not used in {}", name, directory));
+        }
+    } else if let Some(_list_matches) =
+        matches.subcommand_matches(ProfileSubCommands::List.get_string())
+    {
+        let current = current.as_ref().map(|(name, _source)| name.as_str());
+        println!(
+            "{}",
+            describe_profiles(&profiles, &config.existing_profiles(), current)
+        );
+        return;
+    }
+    persistence::save_profiles(file_path, &profiles).unwrap_or_else(|e| exit_with_error(e));
+}
+
+fn load_profiles(config: &Config) -> ProfileCollection {
+    match config.profiles_file_path() {
+        Some(file_path) => {
+            persistence::load_profiles(file_path).unwrap_or_else(|e| exit_with_error(e))
+        }
+        None => ProfileCollection::new(),
+    }
+}
+
+/// The profile name and the directory, made absolute unless it starts from the home directory
+fn profile_and_directory(matches: &ArgMatches) -> (String, String) {
+    let name = required_value(matches, "name");
+    if !is_valid_profile_name(name) || name == DEFAULT_PROFILE {
+        exit_with_error(format!("Invalid profile '{}'", name));
+    }
+    let directory = required_value(matches, "directory");
+    let directory = if directory == "~" || directory.starts_with("~/") {
+        directory.trim_end_matches('/').to_string()
+    } else {
+        let path = env::current_dir()
+            .unwrap_or_default()
+            .join(Path::new(directory));
+        fs::canonicalize(&path)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    (name.to_string(), directory)
+}
+
+fn describe_profiles(
+    profiles: &ProfileCollection,
+    existing: &[String],
+    current: Option<&str>,
+) -> String {
+    let mut names: Vec<&String> = profiles.profiles().iter().map(|p| p.name()).collect();
+    let without_directories: Vec<&String> = existing
+        .iter()
+        .filter(|name| profiles.find_profile(name).is_none())
+        .collect();
+    names.extend(without_directories);
+    names
+        .iter()
+        .map(|name| {
+            let marker = if current == Some(name.as_str()) {
+                " (current)"
+            } else {
+                ""
+            };
+            let directories = profiles
+                .find_profile(name)
+                .map(|profile| {
+                    profile
+                        .directories()
+                        .iter()
+                        .map(|directory| format!("\n    - {}", directory))
+                        .collect::<String>()
+                })
+                .unwrap_or_default();
+            if directories.is_empty() {
+                format!("- name: {}{}", name, marker)
+            } else {
+                format!("- name: {}{}\n  directories:{}", name, marker, directories)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n---\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pair_commit_tool::models::profile::{Profile, ProfileCollection};
+
+    use crate::cli::profile::{describe_profile, describe_profiles, ProfileSource};
+
+    #[test]
+    fn test_describe_profiles() {
+        let mut work = Profile::new("work");
+        work.add_directory("~/work");
+        let profiles = ProfileCollection::from(vec![work]);
+        let existing = vec![String::from("oss"), String::from("work")];
+        let expected = "- name: work (current)\n  \
+                        directories:\n    \
+                        - ~/work\n\
+                        ---\n\
+                        - name: oss";
+        assert_eq!(
+            expected,
+            describe_profiles(&profiles, &existing, Some("work"))
+        );
+    }
+
+    #[test]
+    fn test_describe_profile() {
+        let profile = Some((
+            String::from("work"),
+            ProfileSource::Directory(PathBuf::from("/src/work")),
+        ));
+        assert_eq!(
+            "Using profile work (directory /src/work)",
+            describe_profile(&profile)
+        );
+        assert_eq!("Using the default profile", describe_profile(&None));
+    }
+}
@@ -0,0 +1,254 @@
+use std::path::Path;
+
+use chrono::{DateTime, Local, Utc};
+
+use pair_commit_tool::models::author::author_collection::AuthorCollection;
+use pair_commit_tool::models::author::role::Role;
+use pair_commit_tool::models::session::{Session, SessionExpiry};
+
+use crate::cli::{exit_with_error, lock_or_exit};
+use crate::persistence;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+pub fn load_session(file_path: &Path) -> Session {
+    persistence::load_session(file_path.to_path_buf()).unwrap_or_else(|e| exit_with_error(e))
+}
+
+/// Loads the session, starting it now when co-authors are active without a start time, such as
+/// those activated before sessions were recorded
+///
+/// The start time is only kept once the session is saved, such as when a commit is recorded, so
+/// that loading never writes.
+pub fn load_current_session(authors: &AuthorCollection, file_path: &Path) -> Session {
+    let session = load_session(file_path);
+    if session.started().is_some() || authors.active_authors().is_empty() {
+        return session;
+    }
+    session.restart(Utc::now(), &authors.active_ids())
+}
+
+/// Starts a new session when co-authors were activated, keeping the roles of those that stay
+/// active, and clears it once nobody is active
+pub fn update_session(authors: &AuthorCollection, activated: bool, file_path: &Path) {
+    let _lock = lock_or_exit(file_path);
+    let session = if authors.active_authors().is_empty() {
+        Session::default()
+    } else if activated {
+        load_session(file_path).restart(Utc::now(), &authors.active_ids())
+    } else {
+        return;
+    };
+    persistence::save_session(file_path.to_path_buf(), &session)
+        .unwrap_or_else(|e| exit_with_error(e));
+}
+
+/// The authors as seen by commits, with their roles in the session and nobody active once the
+/// session expired
+pub fn session_authors(
+    mut authors: AuthorCollection,
+    session: &Session,
+    expiry: SessionExpiry,
+) -> AuthorCollection {
+    if session.is_expired(expiry, Utc::now()) {
+        authors.deactivate_all();
+    }
+    authors.set_roles_by_ids(session.roles());
+    authors
+}
+
+/// Gives active co-authors a role for the rest of the session
+pub fn handle_role_sub_command(
+    authors: &AuthorCollection,
+    indexes: &[usize],
+    role: Role,
+    file_path: &Path,
+) {
+    let inactive: Vec<String> = indexes
+        .iter()
+        .map(|&index| &authors.authors()[index])
+        .filter(|author| !author.active::<bool>())
+        .map(|author| author.to_string())
+        .collect();
+    if !inactive.is_empty() {
+        exit_with_error(format!(
+            "Only active co-authors have a role, {} is not active",
+            inactive.join(", ")
+        ));
+    }
+    let _lock = lock_or_exit(file_path);
+    let mut session = load_session(file_path);
+    for &index in indexes {
+        let author = &authors.authors()[index];
+        session.set_role(author.id(), role);
+        println!("{} is now {}", author, role);
+    }
+    persistence::save_session(file_path.to_path_buf(), &session)
+        .unwrap_or_else(|e| exit_with_error(e));
+}
+
+pub fn handle_status_sub_command(
+    authors: &AuthorCollection,
+    session: &Session,
+    expiry: SessionExpiry,
+    scope: &str,
+) {
+    println!("{}", scope);
+    println!("{}", describe_status(authors, session, expiry, Utc::now()));
+}
+
+fn describe_status(
+    authors: &AuthorCollection,
+    session: &Session,
+    expiry: SessionExpiry,
+    now: DateTime<Utc>,
+) -> String {
+    let active = authors.active_authors();
+    if active.is_empty() {
+        return String::from("No active co-authors");
+    }
+
+    let mut lines = Vec::new();
+    let expires_at = session.expires_at(expiry);
+    match expires_at {
+        Some(expires_at) if expires_at <= now => {
+            lines.push(format!(
+                "Session expired at {} ({}), so no co-authors are added to commits",
+                format_time(expires_at),
+                expiry
+            ));
+            lines.push(String::from(
+                "Use with, activate or configure to start a new session with:",
+            ));
+        }
+        _ => lines.push(String::from("Active co-authors:")),
+    }
+    lines.extend(active.iter().map(|author| {
+        if authors.is_committer(author) {
+            format!("  {} (committer, not credited)", author)
+        } else {
+            match session.role(author.id()) {
+                Role::Driver => format!("  {}", author),
+                role => format!("  {} ({})", author, role),
+            }
+        }
+    }));
+    if let Some(started) = session.started() {
+        lines.push(format!("Session started at {}", format_time(started)));
+    }
+    if let Some(last_commit) = session.last_commit() {
+        lines.push(format!("Last commit at {}", format_time(last_commit)));
+    }
+    match expires_at {
+        Some(expires_at) if expires_at > now => lines.push(format!(
+            "Session expires at {} ({})",
+            format_time(expires_at),
+            expiry
+        )),
+        Some(_) => {}
+        None => lines.push(String::from("Session does not expire")),
+    }
+    lines.join("\n")
+}
+
+pub fn format_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format(TIME_FORMAT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    use pair_commit_tool::models::author::role::Role;
+    use pair_commit_tool::models::author::ActiveState::ACTIVE;
+    use pair_commit_tool::models::author::Author;
+    use pair_commit_tool::models::session::{Session, SessionExpiry};
+
+    use crate::cli::session::{describe_status, session_authors};
+
+    fn authors() -> AuthorCollection {
+        AuthorCollection::from(vec![Author::with_active_state(
+            "Tester",
+            "tester@test.com",
+            ACTIVE,
+        )])
+    }
+
+    #[test]
+    fn test_session_authors_expired() {
+        let session = Session::start(Utc::now() - Duration::hours(9));
+        let expiry = SessionExpiry::After(Duration::hours(8));
+        assert!(session_authors(authors(), &session, expiry)
+            .active_authors()
+            .is_empty());
+        assert_eq!(
+            1,
+            session_authors(authors(), &session, SessionExpiry::Never)
+                .active_authors()
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_describe_status_expired() {
+        let now = Utc::now();
+        let session = Session::start(now - Duration::hours(9));
+        let expiry = SessionExpiry::After(Duration::hours(8));
+        let status = describe_status(&authors(), &session, expiry, now);
+        assert!(status.starts_with("Session expired at "));
+        assert!(status.contains("(after 8h)"));
+        assert!(status.contains("  Tester <tester@test.com>"));
+    }
+
+    #[test]
+    fn test_describe_status_without_expiry() {
+        let status = describe_status(
+            &authors(),
+            &Session::default(),
+            SessionExpiry::Never,
+            Utc::now(),
+        );
+        assert_eq!(
+            "Active co-authors:\n  Tester <tester@test.com>\nSession does not expire",
+            status
+        );
+    }
+
+    #[test]
+    fn test_session_roles() {
+        let mut authors = authors();
+        authors.assign_missing_ids();
+        let id = authors.authors()[0].id().clone();
+        let mut session = Session::default();
+        session.set_role(&id, Role::Navigator);
+        let status = describe_status(&authors, &session, SessionExpiry::Never, Utc::now());
+        assert!(status.contains("  Tester <tester@test.com> (navigator)"));
+        let authors = session_authors(authors, &session, SessionExpiry::Never);
+        assert_eq!(Role::Navigator, authors.authors()[0].role());
+    }
+
+    #[test]
+    fn test_describe_status_committer() {
+        let mut authors = authors();
+        authors.set_committer(Some(String::from("TESTER@test.com")));
+        let status = describe_status(
+            &authors,
+            &Session::default(),
+            SessionExpiry::Never,
+            Utc::now(),
+        );
+        assert!(status.contains("  Tester <tester@test.com> (committer, not credited)"));
+    }
+
+    #[test]
+    fn test_describe_status_nobody_active() {
+        let status = describe_status(
+            &AuthorCollection::new(),
+            &Session::default(),
+            SessionExpiry::EndOfDay,
+            Utc::now(),
+        );
+        assert_eq!("No active co-authors", status);
+    }
+}
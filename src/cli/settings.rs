@@ -0,0 +1,204 @@
+use std::env;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use serde_yaml::Value;
+
+use pair_commit_tool::models::settings::{
+    environment_variable, set_setting, setting_keys, setting_layer, SettingOrigin, SettingsLayers,
+};
+
+use crate::cli::{exit_with_error, file_path_or_exit, required_value};
+use crate::config::Config;
+use crate::error::Error;
+use crate::persistence;
+
+pub enum ConfigSubCommands {
+    Get,
+    Set,
+    List,
+}
+
+impl ConfigSubCommands {
+    pub fn get_string(&self) -> &str {
+        match self {
+            ConfigSubCommands::Get => "get",
+            ConfigSubCommands::Set => "set",
+            ConfigSubCommands::List => "list",
+        }
+    }
+}
+
+fn key_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("key")
+        .required(true)
+        .value_name("KEY")
+        .help("Setting such as trailer.key or session.expiry")
+}
+
+fn show_origin_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("show-origin")
+        .long("show-origin")
+        .takes_value(false)
+        .help("Show where each value comes from")
+}
+
+pub fn config_sub_command<'a, 'b>(name: &'a str) -> App<'a, 'b> {
+    SubCommand::with_name(name)
+        .about("Show and change settings")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name(ConfigSubCommands::Get.get_string())
+                .about("Show the value of a setting")
+                .arg(key_arg())
+                .arg(show_origin_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name(ConfigSubCommands::Set.get_string())
+                .about(
+                    "Change a setting in config.yml, or in profiles/<PROFILE>/config.yml with \
+                     --profile",
+                )
+                .arg(key_arg())
+                .arg(
+                    Arg::with_name("value")
+                        .required(true)
+                        .value_name("VALUE")
+                        .help("New value of the setting"),
+                )
+                .arg(
+                    Arg::with_name("repo")
+                        .long("repo")
+                        .takes_value(false)
+                        .help("Change the setting in the .pair-commit.yml of the repository"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(ConfigSubCommands::List.get_string())
+                .about("List every setting with its value")
+                .arg(show_origin_arg()),
+        )
+}
+
+/// Loads the settings from the defaults, the settings files, `PAIR_COMMIT_*` environment
+/// variables and the `KEY=VALUE` overrides given on the command line, in that order
+pub fn load_settings_layers(config: &Config, overrides: &[String]) -> SettingsLayers {
+    let mut layers = SettingsLayers::new();
+    let project_file_path = config.project_file_path();
+    for file_path in config.settings_file_paths() {
+        let layer = persistence::load_settings_layer(file_path.clone())
+            .unwrap_or_else(|e| exit_with_error(e));
+        let is_project = project_file_path.as_ref() == Some(&file_path);
+        let origin = SettingOrigin::File(file_path);
+        let pushed = if is_project {
+            layers.push_project(origin, layer)
+        } else {
+            layers.push(origin, layer)
+        };
+        pushed.unwrap_or_else(|e| exit_with_error(Error::Config(e)));
+    }
+    for key in setting_keys() {
+        let variable = environment_variable(&key);
+        if let Ok(value) = env::var(&variable) {
+            let layer = setting_layer(&key, &value);
+            push_or_exit(&mut layers, SettingOrigin::Environment(variable), layer);
+        }
+    }
+    for setting in overrides {
+        let layer = match setting.split_once('=') {
+            Some((key, value)) => setting_layer(key.trim(), value),
+            None => Err(format!("Expected KEY=VALUE, found '{}'", setting)),
+        };
+        push_or_exit(&mut layers, SettingOrigin::CommandLine, layer);
+    }
+    layers
+}
+
+fn push_or_exit(layers: &mut SettingsLayers, origin: SettingOrigin, layer: Result<Value, String>) {
+    let layer =
+        layer.unwrap_or_else(|e| exit_with_error(Error::Config(format!("{} ({})", e, origin))));
+    layers
+        .push(origin, layer)
+        .unwrap_or_else(|e| exit_with_error(Error::Config(e)));
+}
+
+pub fn handle_config_sub_command(layers: &SettingsLayers, matches: &ArgMatches, config: &Config) {
+    if let Some(get_matches) = matches.subcommand_matches(ConfigSubCommands::Get.get_string()) {
+        let key = required_value(get_matches, "key");
+        let (value, origin) = layers
+            .get(key)
+            .unwrap_or_else(|| exit_with_error(format!("Unknown setting '{}'", key)));
+        println!(
+            "{}",
+            describe_setting(None, &value, origin, get_matches.is_present("show-origin"))
+        );
+    } else if let Some(set_matches) =
+        matches.subcommand_matches(ConfigSubCommands::Set.get_string())
+    {
+        let key = required_value(set_matches, "key");
+        let value = required_value(set_matches, "value");
+        let file_path = if set_matches.is_present("repo") {
+            config.repository_project_file_path().unwrap_or_else(|| {
+                exit_with_error(Error::Git(String::from(
+                    "--repo needs to be run inside a git repository",
+                )))
+            })
+        } else {
+            file_path_or_exit(config.settings_file_path())
+        };
+        let mut layer = persistence::load_settings_layer(file_path.clone())
+            .unwrap_or_else(|e| exit_with_error(e));
+        set_setting(&mut layer, key, value).unwrap_or_else(|e| exit_with_error(e));
+        persistence::save_settings_layer(file_path.clone(), &layer)
+            .unwrap_or_else(|e| exit_with_error(e));
+        println!("Set {} in {}", key, file_path.display());
+    } else if let Some(list_matches) =
+        matches.subcommand_matches(ConfigSubCommands::List.get_string())
+    {
+        let show_origin = list_matches.is_present("show-origin");
+        for (key, value, origin) in layers.list() {
+            println!(
+                "{}",
+                describe_setting(Some(&key), &value, origin, show_origin)
+            );
+        }
+    }
+}
+
+fn describe_setting(
+    key: Option<&str>,
+    value: &str,
+    origin: &SettingOrigin,
+    show_origin: bool,
+) -> String {
+    let setting = match key {
+        Some(key) => format!("{}={}", key, value),
+        None => value.to_string(),
+    };
+    if show_origin {
+        format!("{}\t{}", origin, setting)
+    } else {
+        setting
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pair_commit_tool::models::settings::SettingOrigin;
+
+    use crate::cli::settings::describe_setting;
+
+    #[test]
+    fn test_describe_setting() {
+        let origin = SettingOrigin::File(PathBuf::from("/home/tester/config.yml"));
+        assert_eq!(
+            "file:/home/tester/config.yml\ttrailer.key=Paired-with",
+            describe_setting(Some("trailer.key"), "Paired-with", &origin, true)
+        );
+        assert_eq!(
+            "end-of-day",
+            describe_setting(None, "end-of-day", &SettingOrigin::Default, false)
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use std::path::{Path, PathBuf};
+
+use pair_commit_tool::models::author::author_collection::AuthorCollection;
+use pair_commit_tool::models::settings::StateScope;
+
+use crate::cli::{exit_with_error, file_path_or_exit, lock_or_exit};
+use crate::config::Config;
+use crate::error::Error;
+use crate::git;
+use crate::persistence::{self, FileLock};
+
+const STATE_DIR_NAME: &str = "pair-commit";
+const ACTIVE_FILE_NAME: &str = "active.yml";
+const SESSION_FILE_NAME: &str = "session.yml";
+const BRANCH_PAIRS_FILE_NAME: &str = "branches.yml";
+const PROFILES_DIR_NAME: &str = "profiles";
+
+/// Where the authors, the active co-authors and their session are kept
+///
+/// Outside of the global scope, the active co-authors and their session are kept in the git
+/// directory of the repository or worktree, and the global ones are used until co-authors are
+/// chosen there. Co-authors bound to branches are always kept in the repository. Each profile
+/// keeps its own files in the git directory, as ids only refer to the co-authors of one profile.
+pub struct Store {
+    data_file: PathBuf,
+    keep_backups: bool,
+    global_session_file: PathBuf,
+    project_file: Option<PathBuf>,
+    scope: StateScope,
+    state_dir: Option<PathBuf>,
+    repository_state_dir: Option<PathBuf>,
+}
+
+impl Store {
+    pub fn new(config: &Config, scope: StateScope) -> Store {
+        let git_dir = match scope {
+            StateScope::Global => None,
+            StateScope::Repository => git::common_git_dir().ok(),
+            StateScope::Worktree => git::git_dir().ok(),
+        };
+        let state_dir_name = match config.profile() {
+            Some(profile) => Path::new(STATE_DIR_NAME)
+                .join(PROFILES_DIR_NAME)
+                .join(profile),
+            None => PathBuf::from(STATE_DIR_NAME),
+        };
+        Store {
+            data_file: file_path_or_exit(config.save_file_path()),
+            keep_backups: !config.uses_data_file(),
+            global_session_file: file_path_or_exit(config.session_file_path()),
+            project_file: config.project_file_path(),
+            scope,
+            state_dir: git_dir.map(|dir| dir.join(&state_dir_name)),
+            repository_state_dir: git::common_git_dir()
+                .ok()
+                .map(|dir| dir.join(&state_dir_name)),
+        }
+    }
+
+    /// Locks the authors file until the lock is dropped, so that runs of the tool that change the
+    /// authors take turns
+    ///
+    /// The lock is taken before loading the authors that are changed and held until they are
+    /// saved, but never while waiting for input.
+    pub fn lock_authors(&self) -> FileLock {
+        lock_or_exit(&self.data_file)
+    }
+
+    /// Loads the authors with the active co-authors of the scope, along with any new authors from
+    /// the project file, which are only saved once they are activated or changed
+    pub fn load_authors(&self) -> AuthorCollection {
+        let mut authors =
+            persistence::load(self.data_file.clone()).unwrap_or_else(|e| exit_with_error(e));
+        if let Some(project_file) = &self.project_file {
+            let project = persistence::load_project(project_file.clone())
+                .unwrap_or_else(|e| exit_with_error(e));
+            for issue in project.import_authors(&mut authors) {
+                eprintln!("Warning: in {}, {}", project_file.display(), issue);
+            }
+        }
+        if let Some(active_ids) = self.load_local_active() {
+            authors.set_active_authors_by_ids(&active_ids);
+        }
+        authors
+    }
+
+    /// Saves the authors, keeping the active co-authors of other scopes as they are
+    pub fn save_authors(&self, authors: &mut AuthorCollection) {
+        self.try_save_authors(authors, &[])
+            .unwrap_or_else(|e| exit_with_error(e));
+    }
+
+    /// Saves the authors after some were merged into others, given as pairs of the merged id and
+    /// the id of the author it was merged into, so that the active co-authors of every scope the
+    /// store knows of and the roles in their sessions refer to the remaining authors
+    pub fn save_merged_authors(
+        &self,
+        authors: &mut AuthorCollection,
+        replaced: &[(String, String)],
+    ) {
+        self.try_save_authors(authors, replaced)
+            .and_then(|()| self.replace_state_ids(replaced))
+            .unwrap_or_else(|e| exit_with_error(e));
+    }
+
+    fn try_save_authors(
+        &self,
+        authors: &mut AuthorCollection,
+        replaced: &[(String, String)],
+    ) -> Result<(), Error> {
+        let active_file = match self.active_file() {
+            Some(active_file) => active_file,
+            None => return self.save_data_file(authors),
+        };
+        let active_ids = authors.active_ids();
+        let global_active_ids = persistence::load(self.data_file.clone())
+            .map(|saved| replace_ids(saved.active_ids(), replaced))
+            .unwrap_or_default();
+        authors.set_active_authors_by_ids(&global_active_ids);
+        self.save_data_file(authors)?;
+        authors.set_active_authors_by_ids(&active_ids);
+        persistence::save_active(active_file, &active_ids)
+    }
+
+    /// Saves the authors file, keeping backups only of the authors of a profile and not of a file
+    /// given for a single run
+    fn save_data_file(&self, authors: &AuthorCollection) -> Result<(), Error> {
+        if self.keep_backups {
+            persistence::save(self.data_file.clone(), authors)
+        } else {
+            persistence::save_without_backup(self.data_file.clone(), authors)
+        }
+    }
+
+    /// Session of the active co-authors in effect
+    pub fn session_file_path(&self) -> PathBuf {
+        match &self.state_dir {
+            Some(state_dir) if self.has_local_state() => state_dir.join(SESSION_FILE_NAME),
+            _ => self.global_session_file.clone(),
+        }
+    }
+
+    /// Co-authors bound to the branches of the repository, or `None` outside of a repository
+    pub fn branch_pairs_file_path(&self) -> Option<PathBuf> {
+        self.repository_state_dir
+            .as_ref()
+            .map(|dir| dir.join(BRANCH_PAIRS_FILE_NAME))
+    }
+
+    /// Describes which active co-authors are in effect
+    pub fn describe_scope(&self) -> String {
+        let scope = match self.scope {
+            StateScope::Global => return String::from("Using the global co-authors"),
+            StateScope::Repository => "repository",
+            StateScope::Worktree => "worktree",
+        };
+        match &self.state_dir {
+            Some(state_dir) if self.has_local_state() => format!(
+                "Using the co-authors of this {}, kept in {}",
+                scope,
+                state_dir.display()
+            ),
+            Some(_) => format!(
+                "Using the global co-authors until co-authors are chosen for this {}",
+                scope
+            ),
+            None => format!(
+                "Using the global co-authors, as the current directory is not in a {}",
+                scope
+            ),
+        }
+    }
+
+    fn active_file(&self) -> Option<PathBuf> {
+        self.state_dir
+            .as_ref()
+            .map(|state_dir| state_dir.join(ACTIVE_FILE_NAME))
+    }
+
+    fn replace_state_ids(&self, replaced: &[(String, String)]) -> Result<(), Error> {
+        let mut state_dirs: Vec<&PathBuf> = self
+            .state_dir
+            .iter()
+            .chain(&self.repository_state_dir)
+            .collect();
+        state_dirs.dedup();
+        let mut session_files = vec![self.global_session_file.clone()];
+        for state_dir in state_dirs {
+            let active_file = state_dir.join(ACTIVE_FILE_NAME);
+            if let Some(ids) = persistence::load_active(active_file.clone())? {
+                persistence::save_active(active_file, &replace_ids(ids, replaced))?;
+            }
+            session_files.push(state_dir.join(SESSION_FILE_NAME));
+        }
+        for session_file in session_files.into_iter().filter(|path| path.is_file()) {
+            let _lock = persistence::lock(&session_file)?;
+            let mut session = persistence::load_session(session_file.clone())?;
+            let mut changed = false;
+            for (from, to) in replaced {
+                changed |= session.replace_reference(from, to);
+            }
+            if changed {
+                persistence::save_session(session_file, &session)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn has_local_state(&self) -> bool {
+        self.active_file().is_some_and(|path| path.is_file())
+    }
+
+    fn load_local_active(&self) -> Option<Vec<String>> {
+        let active_file = self.active_file()?;
+        persistence::load_active(active_file).unwrap_or_else(|e| exit_with_error(e))
+    }
+}
+
+/// Replaces merged ids with the ids of the authors they were merged into, keeping each id once
+fn replace_ids(ids: Vec<String>, replaced: &[(String, String)]) -> Vec<String> {
+    let mut replaced_ids: Vec<String> = Vec::new();
+    for id in ids {
+        let id = replaced
+            .iter()
+            .find(|(from, _to)| from.eq_ignore_ascii_case(&id))
+            .map_or(id, |(_from, to)| to.clone());
+        if !replaced_ids.contains(&id) {
+            replaced_ids.push(id);
+        }
+    }
+    replaced_ids
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    use pair_commit_tool::models::author::role::Role;
+    use pair_commit_tool::models::author::Author;
+    use pair_commit_tool::models::session::Session;
+    use pair_commit_tool::models::settings::StateScope;
+
+    use crate::cli::store::{Store, SESSION_FILE_NAME, STATE_DIR_NAME};
+
+    fn store_in(dir: &Path, scope: StateScope) -> Store {
+        Store {
+            data_file: dir.join("data.yml"),
+            keep_backups: true,
+            global_session_file: dir.join(SESSION_FILE_NAME),
+            project_file: None,
+            scope,
+            state_dir: Some(dir.join("git").join(STATE_DIR_NAME))
+                .filter(|_| scope != StateScope::Global),
+            repository_state_dir: Some(dir.join("git").join(STATE_DIR_NAME)),
+        }
+    }
+
+    fn store_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("pair-commit-tool-store-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("git")).unwrap();
+        dir
+    }
+
+    fn two_authors(store: &Store) -> AuthorCollection {
+        let mut authors = AuthorCollection::new();
+        authors.add_author(Author::new("Tester", "tester@test.com"));
+        authors.add_author(Author::new("Other", "other@test.com"));
+        authors.set_active_authors_by_indexes(&[0]);
+        crate::persistence::save(store.data_file.clone(), &authors).unwrap();
+        authors
+    }
+
+    #[test]
+    fn test_local_state_falls_back_to_global() {
+        let dir = store_dir("fallback");
+        let store = store_in(&dir, StateScope::Repository);
+        let authors = two_authors(&store);
+        assert_eq!(authors.active_ids(), store.load_authors().active_ids());
+        assert_eq!(dir.join("session.yml"), store.session_file_path());
+        assert!(store
+            .describe_scope()
+            .contains("until co-authors are chosen"));
+    }
+
+    #[test]
+    fn test_local_state_keeps_global_active_authors() {
+        let dir = store_dir("local");
+        let store = store_in(&dir, StateScope::Worktree);
+        let global_ids = two_authors(&store).active_ids();
+
+        let mut authors = store.load_authors();
+        authors.set_active_authors_by_indexes(&[1]);
+        store.save_authors(&mut authors);
+
+        let local_ids = store.load_authors().active_ids();
+        assert_eq!(authors.active_ids(), local_ids);
+        assert_ne!(global_ids, local_ids);
+        let global = store_in(&dir, StateScope::Global);
+        assert_eq!(global_ids, global.load_authors().active_ids());
+        assert!(store.session_file_path().starts_with(dir.join("git")));
+    }
+
+    #[test]
+    fn test_data_file_has_no_backups() {
+        let dir = store_dir("backups");
+        let store = Store {
+            keep_backups: false,
+            ..store_in(&dir, StateScope::Global)
+        };
+        let mut authors = two_authors(&store);
+        authors.set_active_authors_by_indexes(&[1]);
+        store.save_authors(&mut authors);
+        assert!(crate::persistence::backups(&store.data_file).is_empty());
+    }
+
+    #[test]
+    fn test_save_merged_authors_replaces_ids() {
+        let dir = store_dir("merged");
+        let store = store_in(&dir, StateScope::Repository);
+        let mut authors = two_authors(&store);
+        authors.add_author(Author::new("T. Ester", "TESTER@test.com"));
+        authors.set_active_authors_by_indexes(&[2]);
+        crate::persistence::save(store.data_file.clone(), &authors).unwrap();
+        store.save_authors(&mut authors);
+        let kept = authors.authors()[0].id().clone();
+        let mut session = Session::default();
+        session.set_role(authors.authors()[2].id(), Role::Navigator);
+        crate::persistence::save_session(store.session_file_path(), &session).unwrap();
+
+        let mut authors = store.load_authors();
+        let replaced: Vec<(String, String)> = authors
+            .dedupe()
+            .into_iter()
+            .map(|(kept, duplicate)| (duplicate.id().clone(), kept))
+            .collect();
+        store.save_merged_authors(&mut authors, &replaced);
+
+        assert_eq!(vec![kept.clone()], store.load_authors().active_ids());
+        let global = store_in(&dir, StateScope::Global);
+        assert_eq!(vec![kept.clone()], global.load_authors().active_ids());
+        let session = crate::persistence::load_session(store.session_file_path()).unwrap();
+        assert_eq!(Role::Navigator, session.role(&kept));
+    }
+}
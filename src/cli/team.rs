@@ -0,0 +1,237 @@
+use std::path::Path;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+use pair_commit_tool::models::author::author_collection::AuthorCollection;
+use pair_commit_tool::models::team::team_collection::TeamCollection;
+use pair_commit_tool::models::team::Team;
+
+use crate::cli::{exit_with_error, find_author_indexes_or_exit, required_value};
+use crate::persistence;
+
+pub enum TeamSubCommands {
+    Create,
+    Delete,
+    Add,
+    Remove,
+    List,
+}
+
+impl TeamSubCommands {
+    pub fn get_string(&self) -> &str {
+        match self {
+            TeamSubCommands::Create => "create",
+            TeamSubCommands::Delete => "delete",
+            TeamSubCommands::Add => "add",
+            TeamSubCommands::Remove => "remove",
+            TeamSubCommands::List => "list",
+        }
+    }
+}
+
+fn team_name_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("name")
+        .required(true)
+        .value_name("NAME")
+        .help("Name of the team")
+}
+
+fn members_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("author")
+        .multiple(true)
+        .value_name("AUTHOR")
+        .help("Index, alias or email of the co-authors")
+}
+
+pub fn team_sub_command<'a, 'b>(name: &'a str) -> App<'a, 'b> {
+    SubCommand::with_name(name)
+        .about("Manage teams of co-authors that can be activated together")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name(TeamSubCommands::Create.get_string())
+                .about("Create a team")
+                .arg(team_name_arg())
+                .arg(members_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name(TeamSubCommands::Delete.get_string())
+                .about("Delete a team")
+                .arg(team_name_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name(TeamSubCommands::Add.get_string())
+                .about("Add co-authors to a team")
+                .arg(team_name_arg())
+                .arg(members_arg().required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name(TeamSubCommands::Remove.get_string())
+                .about("Remove co-authors from a team")
+                .arg(team_name_arg())
+                .arg(members_arg().required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name(TeamSubCommands::List.get_string())
+                .about("List teams with their members"),
+        )
+}
+
+pub fn handle_team_sub_command(
+    authors: &AuthorCollection,
+    mut teams: TeamCollection,
+    matches: &ArgMatches,
+    file_path: &Path,
+) {
+    if let Some(create_matches) = matches.subcommand_matches(TeamSubCommands::Create.get_string()) {
+        let name = required_value(create_matches, "name");
+        let mut team = Team::new(name);
+        for reference in member_references(authors, create_matches) {
+            team.add_member(reference);
+        }
+        if !teams.add_team(team) {
+            exit_with_error(format!("Team '{}' already exists", name));
+        }
+    } else if let Some(delete_matches) =
+        matches.subcommand_matches(TeamSubCommands::Delete.get_string())
+    {
+        let name = required_value(delete_matches, "name");
+        if teams.remove_team(name).is_none() {
+            exit_with_error(format!("No team named '{}'", name));
+        }
+    } else if let Some(add_matches) = matches.subcommand_matches(TeamSubCommands::Add.get_string())
+    {
+        let references = member_references(authors, add_matches);
+        let team = find_team_mut_or_exit(&mut teams, add_matches);
+        for reference in references {
+            team.add_member(reference);
+        }
+    } else if let Some(remove_matches) =
+        matches.subcommand_matches(TeamSubCommands::Remove.get_string())
+    {
+        let references = member_references(authors, remove_matches);
+        let team = find_team_mut_or_exit(&mut teams, remove_matches);
+        for reference in references {
+            team.remove_member(&reference);
+        }
+    } else if let Some(_list_matches) =
+        matches.subcommand_matches(TeamSubCommands::List.get_string())
+    {
+        println!("{}", describe_teams(authors, &teams));
+        return;
+    }
+    persistence::save_teams(file_path.to_path_buf(), &teams).unwrap_or_else(|e| exit_with_error(e));
+}
+
+/// Loads the teams with their members referring to the authors as they are now, which is kept the
+/// next time the teams are saved
+pub fn load_teams(authors: &AuthorCollection, file_path: &Path) -> TeamCollection {
+    let mut teams =
+        persistence::load_teams(file_path.to_path_buf()).unwrap_or_else(|e| exit_with_error(e));
+    teams.update_references(authors);
+    teams
+}
+
+/// Indexes of the authors in the team, exiting if the team does not exist
+pub fn team_member_indexes_or_exit(
+    authors: &AuthorCollection,
+    teams: &TeamCollection,
+    name: &str,
+) -> Vec<usize> {
+    let team = teams
+        .find_team(name)
+        .unwrap_or_else(|| exit_with_error(format!("No team named '{}'", name)));
+    team.members()
+        .iter()
+        .filter_map(|reference| authors.find_author_index_by_reference(reference))
+        .collect()
+}
+
+pub fn member_references(authors: &AuthorCollection, matches: &ArgMatches) -> Vec<String> {
+    let selectors: Vec<&str> = matches
+        .values_of("author")
+        .map(|values| values.collect())
+        .unwrap_or_default();
+    find_author_indexes_or_exit(authors, &selectors)
+        .into_iter()
+        .map(|index| authors.authors()[index].reference().clone())
+        .collect()
+}
+
+fn find_team_mut_or_exit<'a>(teams: &'a mut TeamCollection, matches: &ArgMatches) -> &'a mut Team {
+    let name = required_value(matches, "name");
+    teams
+        .find_team_mut(name)
+        .unwrap_or_else(|| exit_with_error(format!("No team named '{}'", name)))
+}
+
+fn describe_teams(authors: &AuthorCollection, teams: &TeamCollection) -> String {
+    teams
+        .teams()
+        .iter()
+        .map(|team| {
+            let members = team
+                .members()
+                .iter()
+                .map(
+                    |reference| match authors.find_author_index_by_reference(reference) {
+                        Some(index) => format!("\n    - {}", authors.authors()[index]),
+                        None => format!("\n    - {} (unknown)", reference),
+                    },
+                )
+                .collect::<String>();
+            format!("- name: {}\n  members:{}", team.name(), members)
+        })
+        .collect::<Vec<String>>()
+        .join("\n---\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    use pair_commit_tool::models::author::Author;
+    use pair_commit_tool::models::team::team_collection::TeamCollection;
+    use pair_commit_tool::models::team::Team;
+
+    use crate::cli::team::{describe_teams, team_member_indexes_or_exit};
+
+    fn authors() -> AuthorCollection {
+        AuthorCollection::from(vec![
+            Author::new("Tester", "tester@test.com"),
+            Author::new("Other", "other@test.com"),
+        ])
+    }
+
+    fn teams() -> TeamCollection {
+        let mut team = Team::new("core");
+        team.add_member("other@test.com");
+        team.add_member("gone@test.com");
+        TeamCollection::from(vec![team])
+    }
+
+    #[test]
+    fn test_team_member_indexes() {
+        assert_eq!(
+            vec![1],
+            team_member_indexes_or_exit(&authors(), &teams(), "core")
+        );
+    }
+
+    #[test]
+    fn test_team_member_indexes_after_email_change() {
+        let mut authors = authors();
+        authors.authors_mut()[1].set_email("other@example.com");
+        assert_eq!(
+            vec![1],
+            team_member_indexes_or_exit(&authors, &teams(), "core")
+        );
+    }
+
+    #[test]
+    fn test_describe_teams() {
+        let expected = "- name: core\n  \
+                        members:\n    \
+                        - Other <other@test.com>\n    \
+                        - gone@test.com (unknown)";
+        assert_eq!(expected, describe_teams(&authors(), &teams()));
+    }
+}
@@ -1,38 +1,69 @@
 use std::io;
-use std::io::{BufRead, Write};
+use std::io::{BufRead, IsTerminal, Read, Write};
 use std::str::FromStr;
 
-pub fn get_user_input<P: AsRef<str>, T: FromStr + Default>(prompt: P) -> Vec<T>
-where
-    <T as std::str::FromStr>::Err: std::fmt::Debug,
-{
+use crate::cli::exit_with_error;
+use crate::error::Error;
+
+/// Checks if stdin is connected to a terminal that can answer prompts
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal()
+}
+
+/// Reads all of stdin and splits it into values separated by commas or whitespace
+pub fn read_values_from_stdin() -> Vec<String> {
+    let mut buf = String::new();
+    io::stdin()
+        .lock()
+        .read_to_string(&mut buf)
+        .unwrap_or_else(|e| exit_with_error(Error::io("Failed to read from stdin", e)));
+    split_values(&buf)
+}
+
+fn split_values(s: &str) -> Vec<String> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+pub fn get_user_input<P: AsRef<str>, T: FromStr>(prompt: P) -> Result<Vec<T>, Vec<T::Err>> {
     print!("{}: ", prompt.as_ref());
     let string: String = read_input_line();
     if string.is_empty() {
-        Vec::new()
+        Ok(Vec::new())
     } else {
         split_string_to_vec(string, ",".parse().unwrap())
     }
 }
 
-fn split_string_to_vec<P: AsRef<str>, T: FromStr + Default>(s: P, split_string: char) -> Vec<T>
-where
-    <T as std::str::FromStr>::Err: std::fmt::Debug,
-{
-    s.as_ref()
+/// Parses every part of the string, returning the errors of all parts that fail
+fn split_string_to_vec<P: AsRef<str>, T: FromStr>(
+    s: P,
+    split_string: char,
+) -> Result<Vec<T>, Vec<T::Err>> {
+    let (values, errors): (Vec<_>, Vec<_>) = s
+        .as_ref()
         .split(split_string)
-        .map(|s| s.trim().parse().unwrap_or_default())
-        .collect::<Vec<T>>()
+        .map(|s| s.trim().parse::<T>())
+        .partition(|result| result.is_ok());
+    if errors.is_empty() {
+        Ok(values.into_iter().filter_map(Result::ok).collect())
+    } else {
+        Err(errors.into_iter().filter_map(Result::err).collect())
+    }
 }
 
 fn read_input_line() -> String {
-    io::stdout().flush().unwrap();
+    io::stdout()
+        .flush()
+        .unwrap_or_else(|e| exit_with_error(Error::io("Failed to write to stdout", e)));
     let stdin = io::stdin();
     let mut buf = String::new();
     stdin
         .lock()
         .read_line(&mut buf)
-        .expect("Cannot read from stdin");
+        .unwrap_or_else(|e| exit_with_error(Error::io("Failed to read from stdin", e)));
     buf.trim().to_owned()
 }
 
@@ -45,14 +76,29 @@ mod tests {
     #[test]
     fn test_split_string_to_vec_i32_space() {
         let string = "1 2 3 4".to_string();
-        let vec: Vec<i32> = split_string_to_vec(string, " ".parse().unwrap());
+        let vec: Vec<i32> = split_string_to_vec(string, " ".parse().unwrap()).unwrap();
         assert_eq!(vec![1, 2, 3, 4], vec);
     }
 
     #[test]
     fn test_split_string_to_vec_i32_comma() {
         let string = "1, 2, 3, 4";
-        let vec: Vec<i32> = split_string_to_vec(string, ",".parse().unwrap());
+        let vec: Vec<i32> = split_string_to_vec(string, ",".parse().unwrap()).unwrap();
         assert_eq!(vec![1, 2, 3, 4], vec);
     }
+
+    #[test]
+    fn test_split_values() {
+        assert_eq!(
+            vec!["jj", "am", "0-2", "tester@test.com"],
+            split_values("jj, am\n0-2\ttester@test.com\n")
+        );
+    }
+
+    #[test]
+    fn test_split_string_to_vec_reports_every_error() {
+        let string = "1, x, 3, y";
+        let errors = split_string_to_vec::<&str, i32>(string, ",".parse().unwrap()).unwrap_err();
+        assert_eq!(2, errors.len());
+    }
 }
@@ -1,112 +1,450 @@
-use std::fmt::Display;
-use std::path::PathBuf;
-use std::{env, error};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
 
-use serde::export::Formatter;
+use crate::error::Error;
+use crate::git;
 
+const PROJECT_FILE_NAME: &str = ".pair-commit.yml";
+const APP_DIR_NAME: &str = "pair-commit-tool";
+const LEGACY_APP_DIR_NAME: &str = ".pair_commit_tool";
+const PROFILES_DIR_NAME: &str = "profiles";
+
+/// Where the files of the tool are kept
+///
+/// Settings, data such as the co-authors and teams, and state such as the session follow the XDG
+/// base directory specification, unless `PAIR_COMMIT_HOME` puts everything in one directory. A
+/// profile keeps its own files in a `profiles/<name>` directory of each of them.
 #[derive(Debug)]
 pub struct Config {
-    app_home: Option<PathBuf>,
+    config_dir: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    state_dir: Option<PathBuf>,
+    legacy_home: Option<PathBuf>,
+    profile: Option<String>,
+    data_file: Option<PathBuf>,
     save_file_name: &'static str,
+    settings_file_name: &'static str,
+    teams_file_name: &'static str,
+    session_file_name: &'static str,
+    profiles_file_name: &'static str,
+    project_file: Option<PathBuf>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
-            app_home: None,
+            config_dir: None,
+            data_dir: None,
+            state_dir: None,
+            legacy_home: None,
+            profile: None,
+            data_file: None,
             save_file_name: "data.yml",
+            settings_file_name: "config.yml",
+            teams_file_name: "teams.yml",
+            session_file_name: "session.yml",
+            profiles_file_name: "profiles.yml",
+            project_file: None,
         }
     }
 }
 
 impl Config {
-    fn get_new_app_home() -> Result<PathBuf, Error> {
-        match env::var("PAIR_COMMIT_HOME") {
-            Ok(s) => Ok(PathBuf::from(s)),
-            Err(_e) => get_default_app_home(),
-        }
+    fn get_project_file() -> Option<PathBuf> {
+        let cwd = env::current_dir().ok()?;
+        let root = git::top_level().ok()?;
+        find_project_file(&cwd, &root)
     }
 
     pub fn new() -> Result<Config, Error> {
-        match Config::get_new_app_home() {
-            Ok(home) => Ok(Config {
-                app_home: Option::from(home),
+        let config = Config::with_base_dirs(|name| env::var(name).ok(), dirs::home_dir())?;
+        Ok(Config {
+            project_file: Config::get_project_file(),
+            ..config
+        })
+    }
+
+    fn with_base_dirs<F: Fn(&str) -> Option<String>>(
+        var: F,
+        home: Option<PathBuf>,
+    ) -> Result<Config, Error> {
+        if let Some(app_home) = var("PAIR_COMMIT_HOME") {
+            let app_home = PathBuf::from(app_home);
+            return Ok(Config {
+                config_dir: Some(app_home.clone()),
+                data_dir: Some(app_home.clone()),
+                state_dir: Some(app_home),
                 ..Config::default()
-            }),
-            Err(e) => Err(e),
+            });
         }
+        let home = home.ok_or_else(|| {
+            Error::Config(String::from(
+                "Failed to find the home directory, set PAIR_COMMIT_HOME instead",
+            ))
+        })?;
+        // Relative paths are invalid according to the specification and have to be ignored
+        let base_dir = |name: &str, default: &str| {
+            var(name)
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .unwrap_or_else(|| home.join(default))
+                .join(APP_DIR_NAME)
+        };
+        Ok(Config {
+            config_dir: Some(base_dir("XDG_CONFIG_HOME", ".config")),
+            data_dir: Some(base_dir("XDG_DATA_HOME", ".local/share")),
+            state_dir: Some(base_dir("XDG_STATE_HOME", ".local/state")),
+            legacy_home: Some(home.join(LEGACY_APP_DIR_NAME)),
+            ..Config::default()
+        })
+    }
+
+    /// Uses the files of the named profile instead of the default ones
+    pub fn with_profile(self, profile: Option<String>) -> Config {
+        Config { profile, ..self }
+    }
+
+    /// Uses the given file for the co-authors instead of the one of the profile
+    pub fn with_data_file(self, data_file: Option<PathBuf>) -> Config {
+        Config { data_file, ..self }
+    }
+
+    pub fn profile(&self) -> Option<&String> {
+        self.profile.as_ref()
+    }
+
+    fn dir_file_path(dir: &Option<PathBuf>, file_name: &str) -> Option<PathBuf> {
+        dir.as_ref().map(|dir| dir.join(file_name))
+    }
+
+    fn profile_file_path(&self, dir: &Option<PathBuf>, file_name: &str) -> Option<PathBuf> {
+        match &self.profile {
+            Some(profile) => dir
+                .as_ref()
+                .map(|dir| dir.join(PROFILES_DIR_NAME).join(profile).join(file_name)),
+            None => Config::dir_file_path(dir, file_name),
+        }
+    }
+
+    /// Whether the authors come from a file given for this run rather than from the profile
+    pub fn uses_data_file(&self) -> bool {
+        self.data_file.is_some()
     }
 
     pub fn save_file_path(&self) -> Option<PathBuf> {
-        match &self.app_home {
-            Some(_h) => {
-                let mut path = PathBuf::new();
-                path.push(&self.app_home.as_ref().unwrap_or(&PathBuf::new()));
-                path.push(&self.save_file_name);
-                Some(path)
-            }
-            None => None,
+        match &self.data_file {
+            Some(data_file) => Some(data_file.clone()),
+            None => self.profile_file_path(&self.data_dir, self.save_file_name),
         }
     }
-}
 
-#[derive(Debug)]
-pub struct Error {
-    message: String,
-}
+    /// Settings of the profile, or the global settings when no profile is used
+    pub fn settings_file_path(&self) -> Option<PathBuf> {
+        self.profile_file_path(&self.config_dir, self.settings_file_name)
+    }
 
-impl Error {
-    fn new(message: String) -> Error {
-        Error { message }
+    pub fn teams_file_path(&self) -> Option<PathBuf> {
+        self.profile_file_path(&self.data_dir, self.teams_file_name)
     }
-}
 
-impl Default for Error {
-    fn default() -> Self {
-        Error {
-            message: "A config error occurred".to_string(),
+    pub fn session_file_path(&self) -> Option<PathBuf> {
+        self.profile_file_path(&self.state_dir, self.session_file_name)
+    }
+
+    /// Profiles and the directories they are used in
+    pub fn profiles_file_path(&self) -> Option<PathBuf> {
+        Config::dir_file_path(&self.config_dir, self.profiles_file_name)
+    }
+
+    /// Names of the profiles that have co-authors or settings
+    pub fn existing_profiles(&self) -> Vec<String> {
+        let mut names: Vec<String> = [&self.data_dir, &self.config_dir]
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir.as_ref()?.join(PROFILES_DIR_NAME)).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Moves the files of the legacy `~/.pair_commit_tool` home directory to the XDG base
+    /// directories, returning the path of every file that was moved along with its new path
+    ///
+    /// Files that already exist in their new place are left where they are, so only the first run
+    /// after upgrading moves anything. The legacy directory is removed once it is empty.
+    pub fn migrate_legacy_home(&self) -> Result<Vec<(PathBuf, PathBuf)>, Error> {
+        let legacy_home = match &self.legacy_home {
+            Some(legacy_home) if legacy_home.is_dir() => legacy_home,
+            _ => return Ok(Vec::new()),
+        };
+        let targets = [
+            (self.save_file_name, self.save_file_path()),
+            (self.teams_file_name, self.teams_file_path()),
+            (self.settings_file_name, self.settings_file_path()),
+            (self.session_file_name, self.session_file_path()),
+        ];
+        let mut moved = Vec::new();
+        for (file_name, target) in targets.iter() {
+            let source = legacy_home.join(file_name);
+            let target = match target {
+                Some(target) if source.is_file() && !target.exists() => target,
+                _ => continue,
+            };
+            move_file(&source, target).map_err(|e| {
+                Error::io(
+                    format!(
+                        "Failed to move {} to {}",
+                        source.display(),
+                        target.display()
+                    ),
+                    e,
+                )
+            })?;
+            moved.push((source, target.clone()));
         }
+        let _ = fs::remove_dir(legacy_home);
+        Ok(moved)
     }
-}
 
-impl Display for Error {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}", self.message)
+    /// The `.pair-commit.yml` of the repository the current directory is in, if there is one
+    pub fn project_file_path(&self) -> Option<PathBuf> {
+        self.project_file.clone()
     }
-}
 
-impl error::Error for Error {}
+    /// Where the `.pair-commit.yml` of the repository is or would be created, if the current
+    /// directory is in a repository
+    pub fn repository_project_file_path(&self) -> Option<PathBuf> {
+        self.project_file_path()
+            .or_else(|| Some(git::top_level().ok()?.join(PROJECT_FILE_NAME)))
+    }
 
-fn get_default_app_home() -> Result<PathBuf, Error> {
-    const APP_DIR_NAME: &str = ".pair_commit_tool";
-    match dirs::home_dir() {
-        Some(path) => {
-            let mut home = path;
-            home.push(APP_DIR_NAME);
-            Ok(home)
+    /// Files containing settings, with later files taking precedence over earlier ones
+    ///
+    /// The settings of a profile are layered over the global settings.
+    pub fn settings_file_paths(&self) -> Vec<PathBuf> {
+        let global = Config::dir_file_path(&self.config_dir, self.settings_file_name);
+        let profile = self.profile.as_ref().and(self.settings_file_path());
+        global
+            .into_iter()
+            .chain(profile)
+            .chain(self.project_file_path())
+            .collect()
+    }
+}
+
+/// Looks for the project file in `start` and its parents, stopping at the repository root
+///
+/// Both paths are canonicalised first, since git reports the root with symlinks resolved.
+fn find_project_file(start: &Path, root: &Path) -> Option<PathBuf> {
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let (start, root) = (canonical(start), canonical(root));
+    for dir in start.ancestors() {
+        let candidate = dir.join(PROJECT_FILE_NAME);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if dir == root {
+            break;
         }
-        None => Err(Error::new(
-            "Failed to get default home directory".to_string(),
-        )),
     }
+    None
+}
+
+/// Moves a file, copying it when it cannot be renamed, such as across file systems
+fn move_file(source: &Path, target: &Path) -> io::Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(source, target).is_err() {
+        fs::copy(source, target)?;
+        fs::remove_file(source)?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use regex::Regex;
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use crate::config::{find_project_file, Config, PROJECT_FILE_NAME};
+    use crate::error::Error;
 
-    use crate::config::{Config, Error};
+    fn config_with(vars: &[(&str, &str)], home: &Path) -> Config {
+        Config::with_base_dirs(
+            |name| {
+                vars.iter()
+                    .find(|(var, _value)| *var == name)
+                    .map(|(_var, value)| value.to_string())
+            },
+            Some(home.to_path_buf()),
+        )
+        .unwrap()
+    }
 
     #[test]
     fn test_save_file_path() {
-        let config = Config::new().unwrap();
-        let re = Regex::new(r"/home/\w*/.pair_commit_tool").unwrap();
+        let config = config_with(&[], Path::new("/home/tester"));
+        assert_eq!(
+            Some(PathBuf::from(
+                "/home/tester/.local/share/pair-commit-tool/data.yml"
+            )),
+            config.save_file_path()
+        );
+    }
+
+    #[test]
+    fn test_settings_file_path() {
+        let config = config_with(&[], Path::new("/home/tester"));
         assert_eq!(
-            true,
-            re.is_match(config.save_file_path().unwrap().to_str().unwrap())
+            Some(PathBuf::from(
+                "/home/tester/.config/pair-commit-tool/config.yml"
+            )),
+            config.settings_file_path()
         );
     }
 
+    #[test]
+    fn test_teams_file_path() {
+        let config = config_with(&[("XDG_DATA_HOME", "/data")], Path::new("/home/tester"));
+        assert_eq!(
+            Some(PathBuf::from("/data/pair-commit-tool/teams.yml")),
+            config.teams_file_path()
+        );
+    }
+
+    #[test]
+    fn test_session_file_path() {
+        let config = config_with(&[("XDG_STATE_HOME", "state")], Path::new("/home/tester"));
+        assert_eq!(
+            Some(PathBuf::from(
+                "/home/tester/.local/state/pair-commit-tool/session.yml"
+            )),
+            config.session_file_path()
+        );
+    }
+
+    #[test]
+    fn test_pair_commit_home_overrides_base_dirs() {
+        let vars = [("PAIR_COMMIT_HOME", "/app"), ("XDG_CONFIG_HOME", "/config")];
+        let config = config_with(&vars, Path::new("/home/tester"));
+        assert_eq!(
+            Some(PathBuf::from("/app/config.yml")),
+            config.settings_file_path()
+        );
+        assert_eq!(
+            Some(PathBuf::from("/app/data.yml")),
+            config.save_file_path()
+        );
+        assert!(config.migrate_legacy_home().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_profile_file_paths() {
+        let config = config_with(&[("PAIR_COMMIT_HOME", "/app")], Path::new("/home/tester"))
+            .with_profile(Some(String::from("work")));
+        assert_eq!(
+            Some(PathBuf::from("/app/profiles/work/data.yml")),
+            config.save_file_path()
+        );
+        assert_eq!(
+            Some(PathBuf::from("/app/profiles/work/session.yml")),
+            config.session_file_path()
+        );
+        assert_eq!(
+            vec![
+                PathBuf::from("/app/config.yml"),
+                PathBuf::from("/app/profiles/work/config.yml")
+            ],
+            config.settings_file_paths()
+        );
+        assert_eq!(
+            Some(PathBuf::from("/app/profiles.yml")),
+            config.profiles_file_path()
+        );
+    }
+
+    #[test]
+    fn test_data_file_overrides_profile() {
+        let config = config_with(&[("PAIR_COMMIT_HOME", "/app")], Path::new("/home/tester"))
+            .with_profile(Some(String::from("work")))
+            .with_data_file(Some(PathBuf::from("/tmp/roster.yml")));
+        assert_eq!(
+            Some(PathBuf::from("/tmp/roster.yml")),
+            config.save_file_path()
+        );
+        assert_eq!(
+            Some(PathBuf::from("/app/profiles/work/teams.yml")),
+            config.teams_file_path()
+        );
+    }
+
+    #[test]
+    fn test_migrate_legacy_home() {
+        let home = env::temp_dir().join("pair-commit-tool-migrate");
+        let _ = fs::remove_dir_all(&home);
+        let legacy = home.join(".pair_commit_tool");
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(legacy.join("data.yml"), "[]").unwrap();
+        fs::write(legacy.join("session.yml"), "{}").unwrap();
+        let config = config_with(&[], &home);
+
+        let moved = config.migrate_legacy_home().unwrap();
+        assert_eq!(2, moved.len());
+        assert!(config.save_file_path().unwrap().is_file());
+        assert!(config.session_file_path().unwrap().is_file());
+        assert!(!legacy.exists());
+        assert!(config.migrate_legacy_home().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate_keeps_existing_files() {
+        let home = env::temp_dir().join("pair-commit-tool-migrate-existing");
+        let _ = fs::remove_dir_all(&home);
+        let legacy = home.join(".pair_commit_tool");
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(legacy.join("config.yml"), "old").unwrap();
+        let config = config_with(&[], &home);
+        let settings_file = config.settings_file_path().unwrap();
+        fs::create_dir_all(settings_file.parent().unwrap()).unwrap();
+        fs::write(&settings_file, "new").unwrap();
+
+        assert!(config.migrate_legacy_home().unwrap().is_empty());
+        assert_eq!("new", fs::read_to_string(settings_file).unwrap());
+        assert!(legacy.join("config.yml").is_file());
+    }
+
+    #[test]
+    fn test_find_project_file_stops_at_root() {
+        let outer = env::temp_dir().join("pair-commit-tool-project");
+        let root = outer.join("repo");
+        let nested = root.join("src").join("models");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(outer.join(PROJECT_FILE_NAME), "").unwrap();
+        let _ = fs::remove_file(root.join(PROJECT_FILE_NAME));
+        assert_eq!(None, find_project_file(&nested, &root));
+
+        fs::write(root.join(PROJECT_FILE_NAME), "").unwrap();
+        assert_eq!(
+            Some(root.join(PROJECT_FILE_NAME)),
+            find_project_file(&nested, &root)
+        );
+    }
+
+    #[test]
+    fn test_find_project_file_compares_canonical_paths() {
+        let outer = env::temp_dir().join("pair-commit-tool-project-canonical");
+        let root = outer.join("repo");
+        let nested = root.join("src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(outer.join(PROJECT_FILE_NAME), "").unwrap();
+        let _ = fs::remove_file(root.join(PROJECT_FILE_NAME));
+        assert_eq!(None, find_project_file(&nested, &nested.join("..")));
+    }
+
     #[test]
     fn test_config_error() {
         let _error: Result<String, Error> = Ok("test".to_string());
@@ -0,0 +1,138 @@
+use std::error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Exit code for invalid input, such as an unknown co-author or an invalid setting value
+pub const EXIT_VALIDATION: i32 = 2;
+/// Exit code when the files of the tool cannot be located, or the settings are invalid
+pub const EXIT_CONFIG: i32 = 3;
+/// Exit code when a file cannot be read or written
+pub const EXIT_IO: i32 = 4;
+/// Exit code when a file does not contain valid data
+pub const EXIT_PARSE: i32 = 5;
+/// Exit code when git fails or the current directory is not in a repository
+pub const EXIT_GIT: i32 = 6;
+
+/// Everything that can go wrong, each kind with its own exit code
+///
+/// Invalid command lines are reported by clap, which exits with 1.
+#[derive(Debug)]
+pub enum Error {
+    /// The files of the tool cannot be located, or the settings are invalid
+    Config(String),
+    /// A file, or stdin, cannot be read or written
+    Io { context: String, source: io::Error },
+    /// A file does not contain valid data
+    Parse {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+    /// Input given by the user is invalid
+    Validation(String),
+    /// Git failed, or the current directory is not in a repository
+    Git(String),
+}
+
+impl Error {
+    pub fn io<S: Into<String>>(context: S, source: io::Error) -> Error {
+        Error::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub fn read(path: &Path, source: io::Error) -> Error {
+        Error::io(format!("Failed to read {}", path.display()), source)
+    }
+
+    pub fn write(path: &Path, source: io::Error) -> Error {
+        Error::io(format!("Failed to write {}", path.display()), source)
+    }
+
+    pub fn parse(path: &Path, source: serde_yaml::Error) -> Error {
+        Error::Parse {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Config(_) => EXIT_CONFIG,
+            Error::Io { .. } => EXIT_IO,
+            Error::Parse { .. } => EXIT_PARSE,
+            Error::Validation(_) => EXIT_VALIDATION,
+            Error::Git(_) => EXIT_GIT,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Error::Config(message) | Error::Validation(message) | Error::Git(message) => {
+                write!(f, "{}", message)
+            }
+            Error::Io { context, source } => write!(f, "{}: {}", context, source),
+            Error::Parse { path, source } => {
+                write!(f, "{} contains invalid data: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Validation(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::Validation(message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::path::Path;
+
+    use crate::error::{Error, EXIT_IO, EXIT_PARSE, EXIT_VALIDATION};
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let io_error = Error::read(Path::new("data.yml"), io::Error::other("denied"));
+        let parse_error = Error::parse(
+            Path::new("data.yml"),
+            serde_yaml::from_str::<Vec<String>>("{").unwrap_err(),
+        );
+        assert_eq!(EXIT_IO, io_error.exit_code());
+        assert_eq!(EXIT_PARSE, parse_error.exit_code());
+        assert_eq!(EXIT_VALIDATION, Error::from("Unknown").exit_code());
+    }
+
+    #[test]
+    fn test_messages() {
+        let error = Error::write(Path::new("/data.yml"), io::Error::other("denied"));
+        assert_eq!("Failed to write /data.yml: denied", error.to_string());
+        let error = Error::parse(
+            Path::new("data.yml"),
+            serde_yaml::from_str::<Vec<String>>("a: b").unwrap_err(),
+        );
+        assert!(error
+            .to_string()
+            .starts_with("data.yml contains invalid data: "));
+    }
+}
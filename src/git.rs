@@ -0,0 +1,163 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::Error;
+
+const AUTHOR_EMAIL_VARIABLE: &str = "GIT_AUTHOR_EMAIL";
+
+fn git(args: &[&str]) -> Result<String, Error> {
+    git_in(Path::new("."), args)
+}
+
+fn git_in(dir: &Path, args: &[&str]) -> Result<String, Error> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| Error::Git(format!("Failed to run git: {}", e)))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(Error::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// Absolute path of a file inside the git directory, as resolved by `git rev-parse --git-path`
+///
+/// This takes `GIT_DIR`, `core.hooksPath` and linked worktrees into account.
+fn git_path(path: &str) -> Result<PathBuf, Error> {
+    absolute(PathBuf::from(git(&["rev-parse", "--git-path", path])?))
+}
+
+fn absolute(resolved: PathBuf) -> Result<PathBuf, Error> {
+    if resolved.is_absolute() {
+        Ok(resolved)
+    } else {
+        let cwd =
+            env::current_dir().map_err(|e| Error::io("Failed to get the current directory", e))?;
+        Ok(cwd.join(resolved))
+    }
+}
+
+pub fn hooks_dir() -> Result<PathBuf, Error> {
+    git_path("hooks")
+}
+
+/// Git directory of the current worktree
+pub fn git_dir() -> Result<PathBuf, Error> {
+    git(&["rev-parse", "--absolute-git-dir"]).map(PathBuf::from)
+}
+
+/// Git directory shared by every worktree of the repository
+pub fn common_git_dir() -> Result<PathBuf, Error> {
+    absolute(PathBuf::from(git(&["rev-parse", "--git-common-dir"])?))
+}
+
+/// Root directory of the working tree the current directory is in
+pub fn top_level() -> Result<PathBuf, Error> {
+    top_level_in(Path::new("."))
+}
+
+fn top_level_in(dir: &Path) -> Result<PathBuf, Error> {
+    git_in(dir, &["rev-parse", "--show-toplevel"]).map(PathBuf::from)
+}
+
+/// Name of the branch checked out in the current worktree, failing when `HEAD` is detached
+pub fn current_branch() -> Result<String, Error> {
+    git(&["symbolic-ref", "--quiet", "--short", "HEAD"])
+}
+
+/// Comment character set with `core.commentChar`, or `None` when it is not set or git picks one
+/// for each message with `auto`
+pub fn comment_char() -> Option<String> {
+    git(&["config", "core.commentChar"])
+        .ok()
+        .filter(|comment_char| !comment_char.is_empty() && comment_char != "auto")
+}
+
+/// Whether a revision names the commit currently checked out
+pub fn is_head(revision: &str) -> bool {
+    let commit = |revision: &str| {
+        git(&[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", revision),
+        ])
+        .ok()
+    };
+    match (commit(revision), commit("HEAD")) {
+        (Some(commit), Some(head)) => commit == head,
+        _ => false,
+    }
+}
+
+/// Email git records as the author of the next commit, from `GIT_AUTHOR_EMAIL` or the
+/// effective `user.email`, or `None` when neither is set
+pub fn author_email() -> Option<String> {
+    author_email_from(env::var(AUTHOR_EMAIL_VARIABLE).ok(), || {
+        git(&["config", "user.email"]).ok()
+    })
+}
+
+/// Picks the author email from the value of `GIT_AUTHOR_EMAIL`, asking for `user.email` only when
+/// it is not set
+fn author_email_from<F: FnOnce() -> Option<String>>(
+    variable: Option<String>,
+    user_email: F,
+) -> Option<String> {
+    variable
+        .filter(|email| !email.trim().is_empty())
+        .or_else(user_email)
+        .filter(|email| !email.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use crate::git::{author_email_from, common_git_dir, git_dir, git_in, hooks_dir, top_level_in};
+
+    #[test]
+    fn test_hooks_dir_is_absolute() {
+        let dir = hooks_dir().unwrap();
+        assert!(dir.is_absolute());
+        assert!(dir.ends_with("hooks"));
+    }
+
+    #[test]
+    fn test_git_dirs_are_absolute() {
+        assert!(git_dir().unwrap().is_absolute());
+        assert!(common_git_dir().unwrap().is_absolute());
+    }
+
+    #[test]
+    fn test_top_level_of_subdirectory() {
+        let repository = env::temp_dir().join("pair-commit-tool-git-top-level");
+        let _ = fs::remove_dir_all(&repository);
+        fs::create_dir_all(repository.join("src")).unwrap();
+        git_in(&repository, &["init", "--quiet"]).unwrap();
+        assert_eq!(
+            repository.canonicalize().unwrap(),
+            top_level_in(&repository.join("src")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_author_email_prefers_environment() {
+        let email = |value: &str| Some(String::from(value));
+        assert_eq!(
+            email("tester@test.com"),
+            author_email_from(email("tester@test.com"), || email("other@test.com"))
+        );
+        assert_eq!(
+            email("other@test.com"),
+            author_email_from(email(" "), || email("other@test.com"))
+        );
+        assert_eq!(None, author_email_from(None, || None));
+    }
+}
@@ -6,6 +6,8 @@ use crate::cli::init;
 
 mod cli;
 mod config;
+mod error;
+mod git;
 mod persistence;
 
 fn main() {
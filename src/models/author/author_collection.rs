@@ -1,9 +1,15 @@
-use std::convert::TryFrom;
+use std::collections::BTreeMap;
 
-use crate::models::author::Author;
+use crate::models::author::role::Role;
+use crate::models::author::selection::{SelectionError, SelectionTarget, SelectionToken};
+use crate::models::author::trailer::TrailerTemplate;
+use crate::models::author::{derive_id, generate_id, normalize_email, Author};
 
+#[derive(Default)]
 pub struct AuthorCollection {
     authors: Vec<Author>,
+    /// Email of the author of the commit, who is not credited as a co-author
+    committer: Option<String>,
 }
 
 impl AuthorCollection {
@@ -14,29 +20,331 @@ impl AuthorCollection {
     }
 
     fn from_vec(vec: Vec<Author>) -> AuthorCollection {
-        AuthorCollection { authors: vec }
+        AuthorCollection {
+            authors: vec,
+            committer: None,
+        }
     }
 
-    pub fn add_author(&mut self, author: Author) {
+    /// Adds an author, giving them a new id unless they already have one
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut authors = AuthorCollection::new();
+    /// authors.add_author(Author::new("Tester", "tester@test.com"));
+    /// assert_eq!(8, authors.authors()[0].id().len());
+    /// ```
+    pub fn add_author(&mut self, mut author: Author) {
+        if author.id.is_empty() {
+            author.id = self.unused_id();
+        }
         self.authors_mut().push(author);
     }
 
+    /// Finds the index of the first author that has any email of the given author, ignoring case
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let authors = AuthorCollection::from(vec![Author::new("Tester", "tester@test.com")]);
+    /// assert_eq!(Some(0), authors.find_duplicate(&Author::new("T. Ester", "Tester@Test.com")));
+    /// assert_eq!(None, authors.find_duplicate(&Author::new("Other", "other@test.com")));
+    /// ```
+    pub fn find_duplicate(&self, author: &Author) -> Option<usize> {
+        self.authors
+            .iter()
+            .position(|existing| existing.shares_email(author))
+    }
+
+    /// Groups of the indexes of authors that share an email, ignoring case, where an author is in
+    /// the group of every author they share an email with
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let authors = AuthorCollection::from(vec![
+    ///     Author::new("Tester", "tester@test.com"),
+    ///     Author::new("Other", "other@test.com"),
+    ///     Author::new("T. Ester", "TESTER@test.com"),
+    /// ]);
+    /// assert_eq!(vec![vec![0, 2]], authors.duplicate_groups());
+    /// ```
+    pub fn duplicate_groups(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for (index, author) in self.authors.iter().enumerate() {
+            let mut group = vec![index];
+            let mut remaining = Vec::new();
+            for existing in groups {
+                if existing
+                    .iter()
+                    .any(|&other| self.authors[other].shares_email(author))
+                {
+                    group.extend(existing);
+                } else {
+                    remaining.push(existing);
+                }
+            }
+            group.sort_unstable();
+            remaining.push(group);
+            groups = remaining;
+        }
+        groups.retain(|group| group.len() > 1);
+        groups.sort_unstable();
+        groups
+    }
+
+    /// Merges every author into the first author they share an email with, returning the
+    /// reference of the author each one was merged into along with the merged author
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut authors = AuthorCollection::from(vec![
+    ///     Author::new("Tester", "tester@test.com"),
+    ///     Author::new("T. Ester", "TESTER@test.com"),
+    /// ]);
+    /// authors.assign_missing_ids();
+    /// let kept = authors.authors()[0].reference().clone();
+    /// let merged = authors.dedupe();
+    /// assert_eq!(kept, merged[0].0);
+    /// assert_eq!("T. Ester", merged[0].1.name());
+    /// assert_eq!(1, authors.authors().len());
+    /// ```
+    pub fn dedupe(&mut self) -> Vec<(String, Author)> {
+        let groups = self.duplicate_groups();
+        let mut merged = Vec::new();
+        let mut removed = Vec::new();
+        for group in groups {
+            if let Some((&kept, duplicates)) = group.split_first() {
+                for &index in duplicates {
+                    let duplicate = std::mem::take(&mut self.authors[index]);
+                    self.authors[kept].merge(&duplicate);
+                    merged.push((self.authors[kept].reference().clone(), duplicate));
+                    removed.push(index);
+                }
+            }
+        }
+        removed.sort_unstable();
+        for index in removed.into_iter().rev() {
+            self.authors.remove(index);
+        }
+        merged
+    }
+
+    /// Gives every author without an id one derived from their email, returning true if any author
+    /// changed
+    ///
+    /// The ids are the same each time the authors are loaded, so they can be referred to before
+    /// they are saved.
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut authors = AuthorCollection::from(vec![Author::new("Tester", "tester@test.com")]);
+    /// assert!(authors.authors()[0].id().is_empty());
+    /// assert!(authors.assign_missing_ids());
+    /// assert!(!authors.authors()[0].id().is_empty());
+    /// assert!(!authors.assign_missing_ids());
+    /// ```
+    pub fn assign_missing_ids(&mut self) -> bool {
+        let mut changed = false;
+        for index in 0..self.authors.len() {
+            if self.authors[index].id.is_empty() {
+                self.authors[index].id = self.unused_derived_id(index);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn unused_derived_id(&self, index: usize) -> String {
+        let mut attempt = 0;
+        loop {
+            if let Some(id) = derive_id(self.authors[index].email(), attempt) {
+                if self.find_author_index_by_id(&id).is_none() {
+                    return id;
+                }
+            }
+            attempt += 1;
+        }
+    }
+
+    fn unused_id(&self) -> String {
+        loop {
+            let id = generate_id();
+            if self.find_author_index_by_id(&id).is_none() {
+                return id;
+            }
+        }
+    }
+
     pub fn authors(&self) -> &Vec<Author> {
         &self.authors
     }
 
+    /// The authors to keep in the authors file, leaving out authors from the project file that
+    /// are neither active nor changed, as the project file keeps providing them
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut project_author = Author::new("Project", "project@test.com");
+    /// project_author.set_from_project(true);
+    /// let mut authors = AuthorCollection::from(vec![project_author]);
+    /// assert!(authors.saved_authors().is_empty());
+    /// authors.authors_mut()[0].activate();
+    /// assert_eq!(1, authors.saved_authors().len());
+    /// ```
+    pub fn saved_authors(&self) -> Vec<&Author> {
+        self.authors
+            .iter()
+            .filter(|author| !author.from_project() || author.active())
+            .collect()
+    }
+
     pub fn authors_mut(&mut self) -> &mut Vec<Author> {
         &mut self.authors
     }
 
     pub fn active_authors(&self) -> Vec<&Author> {
-        self.authors.iter().filter(|a| a.active()).collect()
+        self.authors
+            .iter()
+            .filter(|a| a.active() && !a.archived())
+            .collect()
     }
 
-    pub fn set_active_authors_by_indexes(&mut self, indexes: &[i32]) {
+    /// Sets the email of the author of the commit, so that they are not credited as their own
+    /// co-author
+    pub fn set_committer(&mut self, email: Option<String>) {
+        self.committer = email
+            .map(|email| normalize_email(&email))
+            .filter(|email| !email.is_empty());
+    }
+
+    /// Checks if the author made the commit, by any of their emails and ignoring case
+    pub fn is_committer(&self, author: &Author) -> bool {
+        self.committer
+            .as_ref()
+            .is_some_and(|email| author.has_email(email))
+    }
+
+    /// The active authors credited as co-authors, which leaves out the committer
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::ActiveState::ACTIVE;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut me = Author::with_active_state("Tester", "tester@example.com", ACTIVE);
+    /// me.set_email("tester@test.com");
+    /// let mut authors = AuthorCollection::from(vec![
+    ///     me,
+    ///     Author::with_active_state("Other", "other@test.com", ACTIVE),
+    /// ]);
+    /// authors.set_committer(Some(String::from("Tester@Example.com")));
+    /// assert_eq!(2, authors.active_authors().len());
+    /// assert_eq!("Other", authors.coauthors()[0].name());
+    /// assert_eq!(1, authors.coauthors().len());
+    /// ```
+    pub fn coauthors(&self) -> Vec<&Author> {
+        self.active_authors()
+            .into_iter()
+            .filter(|author| !self.is_committer(author))
+            .collect()
+    }
+
+    /// Finds the index of the author matching an index, id, alias or email
+    ///
+    /// Indexes change when authors are added or removed, so scripts should prefer ids. Ids are
+    /// matched before indexes, so that an id made only of digits is not taken for an index, and
+    /// primary emails are matched before secondary emails.
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let authors = AuthorCollection::from(vec![
+    ///     Author::new("Tester", "tester@test.com"),
+    ///     Author::new("Other", "other@test.com"),
+    /// ]);
+    /// # let mut authors = authors;
+    /// # authors.authors_mut()[1].set_alias(Some("ot"));
+    /// # authors.assign_missing_ids();
+    /// let id = authors.authors()[1].id().clone();
+    /// assert_eq!(Some(1), authors.find_author_index("1"));
+    /// assert_eq!(Some(1), authors.find_author_index(&id));
+    /// assert_eq!(Some(1), authors.find_author_index("ot"));
+    /// assert_eq!(Some(1), authors.find_author_index("Other@Test.com"));
+    /// assert_eq!(None, authors.find_author_index("2"));
+    /// ```
+    pub fn find_author_index(&self, selector: &str) -> Option<usize> {
+        let selector = selector.trim();
+        if let Some(index) = self.find_author_index_by_id(selector) {
+            return Some(index);
+        }
+        if let Ok(index) = selector.parse::<usize>() {
+            return if index < self.authors.len() {
+                Some(index)
+            } else {
+                None
+            };
+        }
+        self.authors
+            .iter()
+            .position(|author| author.has_alias(selector))
+            .or_else(|| {
+                self.authors
+                    .iter()
+                    .position(|author| author.email().eq_ignore_ascii_case(selector))
+            })
+            .or_else(|| {
+                self.authors
+                    .iter()
+                    .position(|author| author.has_email(selector))
+            })
+    }
+
+    pub fn find_author_index_by_id(&self, id: &str) -> Option<usize> {
+        self.authors.iter().position(|author| author.has_id(id))
+    }
+
+    /// Finds the index of the author with the reference returned by [`Author::reference`]
+    ///
+    /// Emails are accepted as well, since they were used as references before authors had ids.
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut authors = AuthorCollection::new();
+    /// authors.add_author(Author::new("Tester", "tester@test.com"));
+    /// let reference = authors.authors()[0].reference().clone();
+    /// authors.authors_mut()[0].set_email("tester@example.com");
+    /// assert_eq!(Some(0), authors.find_author_index_by_reference(&reference));
+    /// assert_eq!(Some(0), authors.find_author_index_by_reference("tester@test.com"));
+    /// ```
+    pub fn find_author_index_by_reference(&self, reference: &str) -> Option<usize> {
+        self.find_author_index_by_id(reference).or_else(|| {
+            self.authors
+                .iter()
+                .position(|author| author.has_email(reference))
+        })
+    }
+
+    /// Checks if an alias is used by any author other than the one at `except`
+    pub fn alias_in_use(&self, alias: &str, except: Option<usize>) -> bool {
+        self.authors
+            .iter()
+            .enumerate()
+            .any(|(index, author)| Some(index) != except && author.has_alias(alias))
+    }
+
+    /// Removes the author at the index from the collection
+    pub fn remove_author(&mut self, index: usize) -> Author {
+        self.authors.remove(index)
+    }
+
+    pub fn set_active_authors_by_indexes(&mut self, indexes: &[usize]) {
         for (index, author) in self.authors.iter_mut().enumerate() {
-            let i32_index: i32 = i32::try_from(index).expect("failed to convert usize to i32");
-            if indexes.contains(&i32_index) {
+            if indexes.contains(&index) && !author.archived() {
                 author.activate()
             } else {
                 author.deactivate()
@@ -44,44 +352,236 @@ impl AuthorCollection {
         }
     }
 
-    pub fn join_all_active_coauthor_strings(&self) -> String {
+    /// Applies a parsed selection to the active state of the authors
+    ///
+    /// Absolute tokens (`all`, `none`, indexes, ranges, aliases and emails) build a new selection,
+    /// otherwise the current active authors are the starting point. `+` and `-` tokens are then
+    /// added and removed. Nothing changes if any token cannot be applied, and every such token is
+    /// reported.
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::selection::SelectionToken;
+    /// # use pair_commit_tool::models::author::ActiveState::ACTIVE;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut authors = AuthorCollection::from(vec![
+    ///     Author::with_active_state("Tester", "tester@test.com", ACTIVE),
+    ///     Author::new("Other", "other@test.com"),
+    ///     Author::new("Third", "third@test.com"),
+    /// ]);
+    /// let tokens: Vec<SelectionToken> = vec!["+2".parse().unwrap(), "-0".parse().unwrap()];
+    /// authors.apply_selection(&tokens).unwrap();
+    /// assert_eq!("third@test.com", authors.active_authors()[0].email());
+    /// assert_eq!(1, authors.active_authors().len());
+    /// ```
+    pub fn apply_selection(
+        &mut self,
+        tokens: &[SelectionToken],
+    ) -> Result<(), Vec<SelectionError>> {
+        let selected = self.resolve_selection(tokens)?;
+        self.set_active_authors_by_indexes(&selected);
+        Ok(())
+    }
+
+    /// Resolves a parsed selection to the indexes of the authors that would be active
+    pub fn resolve_selection(
+        &self,
+        tokens: &[SelectionToken],
+    ) -> Result<Vec<usize>, Vec<SelectionError>> {
+        let mut errors: Vec<SelectionError> = Vec::new();
+        let mut selected: Vec<usize> = if tokens.iter().any(|t| t.is_absolute()) {
+            Vec::new()
+        } else {
+            self.authors
+                .iter()
+                .enumerate()
+                .filter(|(_index, author)| author.active() && !author.archived())
+                .map(|(index, _author)| index)
+                .collect()
+        };
+
+        for token in tokens.iter().filter(|t| t.is_absolute()) {
+            match token {
+                SelectionToken::All => selected.extend(
+                    self.authors
+                        .iter()
+                        .enumerate()
+                        .filter(|(_index, author)| !author.archived())
+                        .map(|(index, _author)| index),
+                ),
+                SelectionToken::None => {}
+                SelectionToken::Set(target) => match self.resolve_target(target) {
+                    Ok(indexes) => selected.extend(indexes),
+                    Err(e) => errors.push(e),
+                },
+                SelectionToken::Add(_) | SelectionToken::Remove(_) => {}
+            }
+        }
+        for token in tokens.iter().filter(|t| !t.is_absolute()) {
+            match token {
+                SelectionToken::Add(target) => match self.resolve_target(target) {
+                    Ok(indexes) => selected.extend(indexes),
+                    Err(e) => errors.push(e),
+                },
+                SelectionToken::Remove(target) => match self.resolve_target(target) {
+                    Ok(indexes) => selected.retain(|index| !indexes.contains(index)),
+                    Err(e) => errors.push(e),
+                },
+                _ => {}
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(selected)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn resolve_target(&self, target: &SelectionTarget) -> Result<Vec<usize>, SelectionError> {
+        match target {
+            SelectionTarget::Range(start, end) => {
+                if *end < self.authors.len() {
+                    Ok((*start..=*end).collect())
+                } else {
+                    Err(SelectionError::OutOfRange(format!("{}-{}", start, end)))
+                }
+            }
+            SelectionTarget::Author(selector) => self
+                .find_author_index(selector)
+                .map(|index| vec![index])
+                .ok_or_else(|| SelectionError::UnknownAuthor(selector.to_string())),
+        }
+    }
+
+    /// Ids of the active authors
+    pub fn active_ids(&self) -> Vec<String> {
         self.active_authors()
             .iter()
-            .map(|author| author.coauthor_string())
-            .collect::<Vec<String>>()
-            .join("\n")
+            .map(|author| author.id().clone())
+            .collect()
+    }
+
+    /// Gives every author the role kept for their id, and the default role to the others
+    /// # Examples
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::role::Role;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut authors = AuthorCollection::new();
+    /// authors.add_author(Author::new("Tester", "tester@test.com"));
+    /// authors.add_author(Author::new("Other", "other@test.com"));
+    /// let id = authors.authors()[1].id().clone();
+    /// authors.set_roles_by_ids(&BTreeMap::from([(id, Role::Reviewer)]));
+    /// assert_eq!(Role::Driver, authors.authors()[0].role());
+    /// assert_eq!(Role::Reviewer, authors.authors()[1].role());
+    /// ```
+    pub fn set_roles_by_ids(&mut self, roles: &BTreeMap<String, Role>) {
+        for author in self.authors.iter_mut() {
+            let role = roles
+                .iter()
+                .find(|(id, _role)| author.has_id(id))
+                .map(|(_id, role)| *role);
+            author.set_role(role.unwrap_or_default());
+        }
+    }
+
+    /// Makes exactly the authors with the given ids active
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut authors = AuthorCollection::new();
+    /// authors.add_author(Author::new("Tester", "tester@test.com"));
+    /// authors.add_author(Author::new("Other", "other@test.com"));
+    /// let id = authors.authors()[1].id().clone();
+    /// authors.set_active_authors_by_ids(&[id.clone()]);
+    /// assert_eq!(vec![id], authors.active_ids());
+    /// ```
+    pub fn set_active_authors_by_ids(&mut self, ids: &[String]) {
+        let indexes: Vec<usize> = ids
+            .iter()
+            .filter_map(|id| self.find_author_index_by_id(id))
+            .collect();
+        self.set_active_authors_by_indexes(&indexes);
     }
 
+    pub fn deactivate_all(&mut self) {
+        self.authors
+            .iter_mut()
+            .for_each(|author| author.deactivate());
+    }
+
+    pub fn join_all_active_coauthor_strings(&self) -> String {
+        self.active_trailers(&TrailerTemplate::default()).join("\n")
+    }
+
+    /// Trailers crediting every co-author, written with the given template
+    pub fn active_trailers(&self, template: &TrailerTemplate) -> Vec<String> {
+        self.coauthors()
+            .iter()
+            .map(|author| template.render(author))
+            .collect()
+    }
+
+    /// Describes every author that is not archived, along with their index
     pub fn authors_with_indexes(&self) -> String {
+        self.describe_authors(false)
+    }
+
+    /// Describes every author, including those that are archived, along with their index
+    pub fn all_authors_with_indexes(&self) -> String {
+        self.describe_authors(true)
+    }
+
+    fn describe_authors(&self, include_archived: bool) -> String {
         self.authors()
             .iter()
             .enumerate()
+            .filter(|(_index, author)| include_archived || !author.archived())
             .map(|(index, author)| {
-                format!(
-                    "- index: {}\n  name: {}\n  email: {}\n  active: {}",
+                let alias = author
+                    .alias()
+                    .map(|alias| format!("\n  alias: {}", alias))
+                    .unwrap_or_default();
+                let id = if author.id().is_empty() {
+                    String::new()
+                } else {
+                    format!("\n  id: {}", author.id())
+                };
+                let mut description = format!(
+                    "- index: {}{}\n  name: {}\n  email: {}{}\n  active: {}",
                     index,
+                    id,
                     author.name(),
                     author.email(),
+                    alias,
                     author.active::<bool>()
-                )
+                );
+                if !author.secondary_emails().is_empty() {
+                    description.push_str(&format!(
+                        "\n  secondary emails: {}",
+                        author.secondary_emails().join(", ")
+                    ));
+                }
+                if author.archived() {
+                    description.push_str("\n  archived: true");
+                }
+                description
             })
             .collect::<Vec<String>>()
             .join("\n---\n")
     }
 }
 
-impl Default for AuthorCollection {
-    fn default() -> Self {
-        AuthorCollection { authors: vec![] }
-    }
-}
-
 impl From<Vec<Author>> for AuthorCollection {
     fn from(vec: Vec<Author>) -> Self {
         AuthorCollection::from_vec(vec)
     }
 }
 
+#[allow(clippy::from_over_into)]
 impl Into<Vec<Author>> for AuthorCollection {
     fn into(self) -> Vec<Author> {
         self.authors
@@ -95,8 +595,10 @@ impl AsRef<AuthorCollection> for AuthorCollection {
 }
 
 #[cfg(test)]
+#[allow(clippy::get_first, clippy::unnecessary_to_owned)]
 mod tests {
     use crate::models::author::author_collection::AuthorCollection;
+    use crate::models::author::selection::{SelectionError, SelectionToken};
     use crate::models::author::ActiveState::ACTIVE;
     use crate::models::author::Author;
 
@@ -113,6 +615,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_join_all_active_coauthor_strings_without_committer() {
+        let mut authors = AuthorCollection::from(vec![
+            Author::with_active_state("Tester", "tester@test.com", ACTIVE),
+            Author::with_active_state("Other", "other@test.com", ACTIVE),
+        ]);
+        authors.set_committer(Some(String::from(" Tester@Test.com\n")));
+        assert_eq!(
+            "Co-authored-by: Other <other@test.com>",
+            authors.join_all_active_coauthor_strings()
+        );
+        authors.set_committer(Some(String::new()));
+        assert_eq!(2, authors.coauthors().len());
+    }
+
+    #[test]
+    fn test_dedupe_follows_secondary_emails() {
+        let mut moved = Author::new("Tester", "tester@example.com");
+        moved.set_email("tester@test.com");
+        let mut authors = AuthorCollection::from(vec![
+            Author::new("Tester", "TESTER@example.com"),
+            Author::new("", ""),
+            Author::new("", ""),
+            Author::new("T. Ester", "tester@test.com"),
+            moved,
+        ]);
+        authors.assign_missing_ids();
+        assert_eq!(vec![vec![0, 3, 4]], authors.duplicate_groups());
+
+        let merged = authors.dedupe();
+        assert_eq!(2, merged.len());
+        assert_eq!(3, authors.authors().len());
+        assert!(authors.authors()[0].has_email("tester@test.com"));
+    }
+
     #[test]
     fn test_set_active_authors_by_indexes() {
         let mut authors = AuthorCollection::from(vec![
@@ -120,7 +657,7 @@ mod tests {
             Author::with_active_state("Tester".to_string(), "tester@test.com".to_string(), ACTIVE),
         ]);
 
-        authors.set_active_authors_by_indexes(&[0 as i32]);
+        authors.set_active_authors_by_indexes(&[0]);
         assert!(authors.authors().get(0).unwrap().active::<bool>());
         assert!(!authors.authors().get(1).unwrap().active::<bool>());
     }
@@ -151,6 +688,184 @@ mod tests {
         assert_eq!(1, collection.authors().len());
     }
 
+    #[test]
+    fn test_active_authors_excludes_archived() {
+        let mut author = Author::with_active_state("Tester", "tester@test.com", ACTIVE);
+        author.archive();
+        author.activate();
+        let authors = AuthorCollection::from(vec![author]);
+        assert!(authors.active_authors().is_empty());
+    }
+
+    #[test]
+    fn test_set_active_authors_by_indexes_skips_archived() {
+        let mut archived = Author::new("Archived", "archived@test.com");
+        archived.archive();
+        let mut authors = AuthorCollection::from(vec![archived]);
+        authors.set_active_authors_by_indexes(&[0]);
+        assert!(!authors.authors()[0].active::<bool>());
+    }
+
+    #[test]
+    fn test_find_author_index_unknown_email() {
+        let authors = AuthorCollection::from(vec![Author::new("Tester", "tester@test.com")]);
+        assert_eq!(None, authors.find_author_index("other@test.com"));
+    }
+
+    #[test]
+    fn test_find_author_index_by_secondary_email() {
+        let mut edited = Author::new("Tester", "tester@test.com");
+        edited.set_email("tester@example.com");
+        let authors = AuthorCollection::from(vec![Author::new("Other", "other@test.com"), edited]);
+        assert_eq!(Some(1), authors.find_author_index("tester@test.com"));
+    }
+
+    #[test]
+    fn test_find_author_index_by_alias_ignores_case() {
+        let mut aliased = Author::new("Tester", "tester@test.com");
+        aliased.set_alias(Some("jj"));
+        let authors = AuthorCollection::from(vec![Author::new("Other", "other@test.com"), aliased]);
+        assert_eq!(Some(1), authors.find_author_index("JJ"));
+    }
+
+    #[test]
+    fn test_add_author_keeps_existing_id() {
+        let mut authors = AuthorCollection::new();
+        authors.add_author(Author::new("Tester", "tester@test.com"));
+        let removed = authors.remove_author(0);
+        let id = removed.id().clone();
+        authors.add_author(removed);
+        assert_eq!(&id, authors.authors()[0].id());
+        assert_eq!(Some(0), authors.find_author_index(&id.to_uppercase()));
+    }
+
+    #[test]
+    fn test_alias_in_use() {
+        let mut aliased = Author::new("Tester", "tester@test.com");
+        aliased.set_alias(Some("jj"));
+        let authors = AuthorCollection::from(vec![aliased, Author::new("Other", "other@test.com")]);
+        assert!(authors.alias_in_use("JJ", None));
+        assert!(authors.alias_in_use("jj", Some(1)));
+        assert!(!authors.alias_in_use("jj", Some(0)));
+        assert!(!authors.alias_in_use("am", None));
+    }
+
+    fn selection(s: &str) -> Vec<SelectionToken> {
+        s.split(',').map(|t| t.parse().unwrap()).collect()
+    }
+
+    fn active_emails(authors: &AuthorCollection) -> Vec<&str> {
+        authors
+            .active_authors()
+            .iter()
+            .map(|a| a.email().as_str())
+            .collect()
+    }
+
+    fn three_authors() -> AuthorCollection {
+        let mut third = Author::new("Third", "third@test.com");
+        third.set_alias(Some("th"));
+        AuthorCollection::from(vec![
+            Author::with_active_state("Tester", "tester@test.com", ACTIVE),
+            Author::new("Other", "other@test.com"),
+            third,
+        ])
+    }
+
+    #[test]
+    fn test_apply_selection_absolute() {
+        let mut authors = three_authors();
+        authors.apply_selection(&selection("1-2")).unwrap();
+        assert_eq!(
+            vec!["other@test.com", "third@test.com"],
+            active_emails(&authors)
+        );
+    }
+
+    #[test]
+    fn test_apply_selection_mixed() {
+        let mut authors = three_authors();
+        authors
+            .apply_selection(&selection("all, -th, +other@test.com"))
+            .unwrap();
+        assert_eq!(
+            vec!["tester@test.com", "other@test.com"],
+            active_emails(&authors)
+        );
+    }
+
+    #[test]
+    fn test_apply_selection_none() {
+        let mut authors = three_authors();
+        authors.apply_selection(&selection("none")).unwrap();
+        assert!(authors.active_authors().is_empty());
+    }
+
+    #[test]
+    fn test_apply_selection_reports_every_error() {
+        let mut authors = three_authors();
+        let errors = authors
+            .apply_selection(&selection("1, x, 0-5, +zz"))
+            .unwrap_err();
+        assert_eq!(
+            vec![
+                SelectionError::UnknownAuthor("x".to_string()),
+                SelectionError::OutOfRange("0-5".to_string()),
+                SelectionError::UnknownAuthor("zz".to_string()),
+            ],
+            errors
+        );
+        assert_eq!(vec!["tester@test.com"], active_emails(&authors));
+    }
+
+    #[test]
+    fn test_apply_selection_all_skips_archived() {
+        let mut authors = three_authors();
+        authors.authors_mut()[1].archive();
+        authors.apply_selection(&selection("all")).unwrap();
+        assert_eq!(
+            vec!["tester@test.com", "third@test.com"],
+            active_emails(&authors)
+        );
+    }
+
+    #[test]
+    fn test_deactivate_all() {
+        let mut authors = AuthorCollection::from(vec![
+            Author::with_active_state("Tester", "tester@test.com", ACTIVE),
+            Author::with_active_state("Other", "other@test.com", ACTIVE),
+        ]);
+        authors.deactivate_all();
+        assert!(authors.active_authors().is_empty());
+    }
+
+    #[test]
+    fn test_remove_author() {
+        let mut authors = AuthorCollection::from(vec![
+            Author::new("Tester", "tester@test.com"),
+            Author::new("Other", "other@test.com"),
+        ]);
+        let removed = authors.remove_author(0);
+        assert_eq!("tester@test.com", removed.email());
+        assert_eq!(1, authors.authors().len());
+    }
+
+    #[test]
+    fn test_authors_with_indexes_hides_archived() {
+        let mut archived = Author::new("Archived", "archived@test.com");
+        archived.archive();
+        let authors =
+            AuthorCollection::from(vec![archived, Author::new("Tester", "tester@test.com")]);
+        let expected = "- index: 1\n  \
+                        name: Tester\n  \
+                        email: tester@test.com\n  \
+                        active: false";
+        assert_eq!(expected, authors.authors_with_indexes());
+        assert!(authors
+            .all_authors_with_indexes()
+            .contains("archived: true"));
+    }
+
     #[test]
     fn test_authors_with_indexes() {
         let authors = AuthorCollection::from(vec![
@@ -1,10 +1,23 @@
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
 use std::fmt::{Display, Error, Formatter};
+use std::hash::{BuildHasher, Hasher};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
 
+use crate::models::author::role::Role;
+use crate::models::author::trailer::{TrailerTemplate, DEFAULT_TRAILER_FORMAT};
 use crate::models::author::ActiveState::{ACTIVE, INACTIVE};
 
 pub mod author_collection;
+pub mod role;
+pub mod selection;
+pub mod trailer;
+
+/// Trailer key git hosts use to credit co-authors
+pub const COAUTHOR_TRAILER_KEY: &str = "Co-authored-by";
 
 /// Stores data of a commit author
 /// # Examples
@@ -15,9 +28,30 @@ pub mod author_collection;
 /// ```
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Author {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    id: String,
     name: String,
     email: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alias: Option<String>,
     active: ActiveState,
+    #[serde(default, skip_serializing_if = "is_false")]
+    archived: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    secondary_emails: Vec<String>,
+    /// Fields written by newer versions of the tool, kept so that saving does not drop them
+    #[serde(flatten)]
+    other: BTreeMap<String, Value>,
+    /// Role in the current session, which is kept with the session rather than the author
+    #[serde(skip)]
+    role: Role,
+    /// Whether the author comes from the project file and has not been changed since
+    #[serde(skip)]
+    from_project: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
 }
 
 impl Display for Author {
@@ -29,9 +63,16 @@ impl Display for Author {
 impl Default for Author {
     fn default() -> Self {
         Author {
+            id: String::new(),
             name: String::from(""),
             email: String::from(""),
+            alias: None,
             active: INACTIVE,
+            archived: false,
+            secondary_emails: Vec::new(),
+            other: BTreeMap::new(),
+            role: Role::default(),
+            from_project: false,
         }
     }
 }
@@ -59,14 +100,210 @@ impl Author {
         &self.email
     }
 
+    /// Stable identifier of the author, assigned when the author is added to a collection
+    pub fn id(&self) -> &String {
+        &self.id
+    }
+
+    /// Checks the id of the author, ignoring case
+    pub fn has_id(&self, id: &str) -> bool {
+        !self.id.is_empty() && self.id.eq_ignore_ascii_case(id)
+    }
+
+    /// Reference used to refer to the author from outside the author collection, such as from
+    /// teams, that stays valid when the author's email changes
+    pub fn reference(&self) -> &String {
+        &self.id
+    }
+
+    /// Short name used to select the author
+    pub fn alias(&self) -> Option<&String> {
+        self.alias.as_ref()
+    }
+
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut author = Author::new("Tester", "tester@test.com");
+    /// author.set_alias(Some("jj"));
+    /// assert_eq!(Some(&"jj".to_string()), author.alias());
+    /// author.set_alias::<&str>(None);
+    /// assert_eq!(None, author.alias());
+    /// ```
+    pub fn set_alias<S: AsRef<str>>(&mut self, alias: Option<S>) {
+        self.alias = alias.map(|a| a.as_ref().to_string());
+        self.from_project = false;
+    }
+
+    /// Checks the alias of the author, ignoring case
+    pub fn has_alias(&self, alias: &str) -> bool {
+        self.alias
+            .as_ref()
+            .is_some_and(|a| a.eq_ignore_ascii_case(alias))
+    }
+
+    /// Whether the author was taken from the project file and not changed since, in which case
+    /// they are only saved once they are active
+    pub fn from_project(&self) -> bool {
+        self.from_project
+    }
+
+    pub fn set_from_project(&mut self, from_project: bool) {
+        self.from_project = from_project;
+    }
+
+    /// Role of the author in the current session
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    pub fn set_role(&mut self, role: Role) {
+        self.role = role;
+    }
+
+    /// Former emails of the author
+    pub fn secondary_emails(&self) -> &Vec<String> {
+        &self.secondary_emails
+    }
+
+    /// Checks the primary and secondary emails of the author, ignoring case
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut author = Author::new("Tester", "tester@test.com");
+    /// author.set_email("tester@example.com");
+    /// assert!(author.has_email("Tester@Test.com"));
+    /// assert!(author.has_email("tester@example.com"));
+    /// assert!(!author.has_email("other@test.com"));
+    /// ```
+    pub fn has_email(&self, email: &str) -> bool {
+        self.email.eq_ignore_ascii_case(email)
+            || self
+                .secondary_emails
+                .iter()
+                .any(|secondary| secondary.eq_ignore_ascii_case(email))
+    }
+
+    /// Checks if the author has any email of the other author, ignoring case and empty emails
+    pub fn shares_email(&self, other: &Author) -> bool {
+        other
+            .emails()
+            .any(|email| !email.is_empty() && self.has_email(email))
+    }
+
+    /// The primary email followed by the secondary emails
+    fn emails(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.email).chain(self.secondary_emails.iter())
+    }
+
+    /// Merges a duplicate of the author into them, keeping the name and primary email while taking
+    /// the emails, alias and fields the author does not have yet
+    ///
+    /// The merged author is active if either was, and archived only if both were.
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::Author;
+    /// # use pair_commit_tool::models::author::ActiveState::ACTIVE;
+    /// let mut author = Author::new("Tester", "tester@test.com");
+    /// let mut duplicate = Author::with_active_state("T. Ester", "Tester@Test.com", ACTIVE);
+    /// duplicate.set_email("tester@example.com");
+    /// duplicate.set_alias(Some("te"));
+    /// author.merge(&duplicate);
+    /// assert_eq!("Tester", author.name());
+    /// assert_eq!(&vec!["tester@example.com".to_string()], author.secondary_emails());
+    /// assert_eq!(Some(&"te".to_string()), author.alias());
+    /// assert!(author.active::<bool>());
+    /// ```
+    pub fn merge(&mut self, duplicate: &Author) {
+        for email in duplicate.emails() {
+            if !email.is_empty() && !self.has_email(email) {
+                self.secondary_emails.push(email.clone());
+            }
+        }
+        if self.alias.is_none() {
+            self.alias = duplicate.alias.clone();
+        }
+        if duplicate.active.into() {
+            self.active = ACTIVE;
+        }
+        self.archived = self.archived && duplicate.archived;
+        for (key, value) in &duplicate.other {
+            self.other
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+
+    /// Changes the name, normalised with [`normalize_name`]
+    pub fn set_name<S: AsRef<str>>(&mut self, name: S) {
+        self.name = normalize_name(name.as_ref());
+        self.from_project = false;
+    }
+
+    /// Changes the primary email, keeping the previous one as a secondary email
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::Author;
+    /// let mut author = Author::new("Tester", "tester@test.com");
+    /// author.set_email("tester@example.com");
+    /// assert_eq!("tester@example.com", author.email());
+    /// assert_eq!(&vec!["tester@test.com".to_string()], author.secondary_emails());
+    /// ```
+    pub fn set_email<S: AsRef<str>>(&mut self, email: S) {
+        self.from_project = false;
+        let email = &normalize_email(email.as_ref());
+        if self.email.eq_ignore_ascii_case(email) {
+            self.email = email.to_string();
+            return;
+        }
+
+        self.secondary_emails
+            .retain(|secondary| !secondary.eq_ignore_ascii_case(email));
+        if !self.email.is_empty() {
+            let previous = std::mem::replace(&mut self.email, email.to_string());
+            self.secondary_emails.push(previous);
+        } else {
+            self.email = email.to_string();
+        }
+    }
+
+    pub fn set_active(&mut self, active: ActiveState) {
+        self.active = active;
+    }
+
+    /// Creates an author with the name and email normalised with [`normalize_name`] and
+    /// [`normalize_email`]
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::Author;
+    /// let author = Author::new("  Zoë\n Ångström ", " zoe@test.com\n");
+    /// assert_eq!("Zoë Ångström", author.name());
+    /// assert_eq!("zoe@test.com", author.email());
+    /// ```
     pub fn new<S: AsRef<str>, T: AsRef<str>>(name: S, email: T) -> Author {
         Author {
-            name: name.as_ref().to_string(),
-            email: email.as_ref().to_string(),
+            name: normalize_name(name.as_ref()),
+            email: normalize_email(email.as_ref()),
             ..Author::default()
         }
     }
 
+    /// Creates an author like [`Author::new`], failing when the normalised name or email cannot be
+    /// credited in a trailer
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::Author;
+    /// assert!(Author::try_new(" Tester ", "tester@test.com").is_ok());
+    /// assert!(Author::try_new("Tester", "tester").is_err());
+    /// assert!(Author::try_new("", "tester@test.com").is_err());
+    /// ```
+    pub fn try_new<S: AsRef<str>, T: AsRef<str>>(name: S, email: T) -> Result<Author, String> {
+        let author = Author::new(name, email);
+        validate_name(author.name())?;
+        validate_email(author.email())?;
+        Ok(author)
+    }
+
     /// # Examples
     /// Active example:
     /// ```
@@ -88,9 +325,10 @@ impl Author {
         active: ActiveState,
     ) -> Author {
         Author {
-            name: name.as_ref().to_string(),
-            email: email.as_ref().to_string(),
+            name: normalize_name(name.as_ref()),
+            email: normalize_email(email.as_ref()),
             active,
+            ..Author::default()
         }
     }
 
@@ -104,6 +342,31 @@ impl Author {
         self.active = INACTIVE;
     }
 
+    pub fn archived(&self) -> bool {
+        self.archived
+    }
+
+    /// Archive author, hiding them from selection without losing their data
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::Author;
+    /// # use pair_commit_tool::models::author::ActiveState::ACTIVE;
+    /// let mut author = Author::with_active_state("Tester", "tester@test.com", ACTIVE);
+    /// author.archive();
+    /// assert!(author.archived());
+    /// assert_eq!(false, author.active());
+    /// ```
+    pub fn archive(&mut self) {
+        self.archived = true;
+        self.from_project = false;
+        self.deactivate();
+    }
+
+    /// Restore an archived author
+    pub fn unarchive(&mut self) {
+        self.archived = false;
+    }
+
     /// # Examples
     /// ```
     /// # use pair_commit_tool::models::author::Author;
@@ -111,10 +374,194 @@ impl Author {
     /// assert_eq!("Co-authored-by: Tester <tester@test.com>", author.coauthor_string());
     /// ```
     pub fn coauthor_string(&self) -> String {
-        return format!("Co-authored-by: {} <{}>", self.name, self.email);
+        TrailerTemplate::default().render(self)
+    }
+
+    /// A single line trailer crediting the author under the given key, in the default format
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::Author;
+    /// let author = Author::new("Tester", "tester@test.com");
+    /// assert_eq!("Paired-with: Tester <tester@test.com>", author.trailer("Paired-with"));
+    /// ```
+    pub fn trailer(&self, key: &str) -> String {
+        TrailerTemplate::new(key, DEFAULT_TRAILER_FORMAT).render(self)
+    }
+}
+
+/// Generates a random identifier of eight hexadecimal digits
+fn generate_id() -> String {
+    loop {
+        let mut hasher = RandomState::new().build_hasher();
+        if let Ok(time) = SystemTime::now().duration_since(UNIX_EPOCH) {
+            hasher.write_u128(time.as_nanos());
+        }
+        if let Some(id) = format_id(hasher.finish()) {
+            return id;
+        }
     }
 }
 
+/// Derives an identifier for an author saved before authors had ids from their email, so that it
+/// is the same every time the file is loaded until the id is saved
+///
+/// Uses FNV-1a, which unlike the hashers of the standard library does not change between releases.
+fn derive_id(email: &str, attempt: u32) -> Option<String> {
+    let hash = normalize_email(email)
+        .to_lowercase()
+        .bytes()
+        .chain(attempt.to_le_bytes())
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format_id(hash)
+}
+
+/// Formats an identifier, which has to contain a letter so that it is never taken for an index
+fn format_id(value: u64) -> Option<String> {
+    let id = format!("{:08x}", value as u32);
+    if id.bytes().all(|b| b.is_ascii_digit()) {
+        None
+    } else {
+        Some(id)
+    }
+}
+
+/// Checks that an alias can be told apart from indexes, emails and selection syntax
+///
+/// The selection keywords `all` and `none` are reserved.
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::author::is_valid_alias;
+/// assert!(is_valid_alias("jj"));
+/// assert!(!is_valid_alias("42"));
+/// assert!(!is_valid_alias("j j"));
+/// assert!(!is_valid_alias("-jj"));
+/// ```
+pub fn is_valid_alias(alias: &str) -> bool {
+    !alias.is_empty()
+        && !alias.chars().all(|c| c.is_ascii_digit())
+        && !alias.starts_with(['+', '-'])
+        && !alias.eq_ignore_ascii_case("all")
+        && !alias.eq_ignore_ascii_case("none")
+        && alias
+            .chars()
+            .all(|c| !c.is_whitespace() && c != ',' && c != '@')
+}
+
+/// Characters that do not show up when a name is displayed but can change how it reads, such as
+/// bidirectional overrides, zero width spaces and byte order marks
+///
+/// Joiners are kept, as some scripts and emoji need them.
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061C}' | '\u{200B}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}'
+            | '\u{2066}'..='\u{2069}' | '\u{FEFF}'
+    )
+}
+
+/// Normalises a name to a single line, where any run of whitespace, including line breaks,
+/// becomes one space and control and invisible characters are dropped
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::author::normalize_name;
+/// assert_eq!("Jo Jones", normalize_name(" Jo\r\n\tJones "));
+/// assert_eq!("李小龍", normalize_name("李小龍\u{202E}"));
+/// assert_eq!("Zoe\u{0308}", normalize_name("Zoe\u{0308}"));
+/// ```
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|&c| c.is_whitespace() || !(c.is_control() || is_invisible(c)))
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Normalises an email by dropping surrounding whitespace and control and invisible characters
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::author::normalize_email;
+/// assert_eq!("tester@test.com", normalize_email(" \u{FEFF}tester@test.com\n"));
+/// ```
+pub fn normalize_email(email: &str) -> String {
+    email
+        .chars()
+        .filter(|&c| !(c.is_control() || is_invisible(c)))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Checks that a normalised name can be written in a trailer, which needs it to be non-empty
+/// and to leave `<` and `>` to the email
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::author::is_valid_name;
+/// assert!(is_valid_name("Zoë Ångström"));
+/// assert!(!is_valid_name(""));
+/// assert!(!is_valid_name("Tester <tester@test.com>"));
+/// ```
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['<', '>'])
+}
+
+/// Checks a normalised name with [`is_valid_name`], explaining what is wrong with it
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if is_valid_name(name) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid name '{}', names cannot be empty or contain '<' or '>'",
+            name
+        ))
+    }
+}
+
+/// Checks a normalised email with [`is_valid_email`], explaining what is wrong with it
+pub fn validate_email(email: &str) -> Result<(), String> {
+    if is_valid_email(email) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid email '{}', emails need a name and a domain such as name@example.com",
+            email
+        ))
+    }
+}
+
+/// Checks that an email has a local part and a domain, as git hosts need to match it to an
+/// account
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::author::is_valid_email;
+/// assert!(is_valid_email("tester@test.com"));
+/// assert!(is_valid_email("12345+tester@users.noreply.github.com"));
+/// assert!(!is_valid_email(""));
+/// assert!(!is_valid_email("tester"));
+/// assert!(!is_valid_email("tester@"));
+/// assert!(!is_valid_email("tester@test..com"));
+/// assert!(!is_valid_email("te ster@test.com"));
+/// assert!(!is_valid_email("<tester@test.com>"));
+/// ```
+pub fn is_valid_email(email: &str) -> bool {
+    let (local, domain) = match email.rsplit_once('@') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    !local.is_empty()
+        && local
+            .chars()
+            .all(|c| !c.is_whitespace() && !c.is_control() && !"<>,;\"@".contains(c))
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
 /// Represents the active state of an author
 /// # Examples
 /// ```
@@ -148,8 +595,10 @@ impl From<ActiveState> for bool {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
+    use crate::models::commit_message::{is_trailer, CommitMessage};
 
     #[test]
     fn test_author_initialisation() {
@@ -176,6 +625,37 @@ mod tests {
         assert_eq!(&email, author.name());
     }
 
+    #[test]
+    fn test_trailer_cannot_be_injected() {
+        assert!(Author::try_new(
+            "Tester\nSigned-off-by: Evil <evil@test.com>",
+            "tester@test.com"
+        )
+        .is_err());
+        assert!(Author::try_new("Tester", "tester@test.com>\nReviewed-by: x").is_err());
+
+        let author = Author::new(
+            "Tester\nSigned-off-by: Evil",
+            "tester@test.com\nReviewed-by: x",
+        );
+        let trailer = author.coauthor_string();
+        let message = CommitMessage::from(format!("Subject\n\n{}\n", trailer));
+        assert_eq!(vec![trailer.as_str()], message.trailers());
+        assert!(is_trailer(&trailer));
+    }
+
+    #[test]
+    fn test_trailer_of_loaded_author_is_normalised() {
+        let author: Author = serde_yaml::from_str(
+            "name: \" Zoë\\u202E\\n Ångström \"\nemail: zoe@test.com\nactive: ACTIVE",
+        )
+        .unwrap();
+        assert_eq!(
+            "Co-authored-by: Zoë Ångström <zoe@test.com>",
+            author.coauthor_string()
+        );
+    }
+
     #[test]
     fn test_author_activate() {
         let mut author = Author::default();
@@ -193,6 +673,102 @@ mod tests {
         assert_eq!(false, author.active());
     }
 
+    #[test]
+    fn test_author_unarchive() {
+        let mut author = Author::default();
+        author.archive();
+        author.unarchive();
+        assert!(!author.archived());
+    }
+
+    #[test]
+    fn test_deserialize_author_without_archived() {
+        let author: Author =
+            serde_yaml::from_str("name: Tester\nemail: tester@test.com\nactive: ACTIVE").unwrap();
+        assert!(!author.archived());
+    }
+
+    #[test]
+    fn test_serialize_archived_author() {
+        let mut author = Author::new("Tester", "tester@test.com");
+        author.archive();
+        let s = serde_yaml::to_string(&author).unwrap();
+        assert!(s.ends_with("archived: true"));
+    }
+
+    #[test]
+    fn test_set_name() {
+        let mut author = Author::new("Tester", "tester@test.com");
+        author.set_name("Renamed");
+        assert_eq!("Renamed", author.name());
+    }
+
+    #[test]
+    fn test_set_email_case_change_keeps_no_secondary() {
+        let mut author = Author::new("Tester", "tester@test.com");
+        author.set_email("Tester@test.com");
+        assert_eq!("Tester@test.com", author.email());
+        assert!(author.secondary_emails().is_empty());
+    }
+
+    #[test]
+    fn test_set_email_back_to_secondary() {
+        let mut author = Author::new("Tester", "tester@test.com");
+        author.set_email("tester@example.com");
+        author.set_email("tester@test.com");
+        assert_eq!("tester@test.com", author.email());
+        assert_eq!(
+            &vec!["tester@example.com".to_string()],
+            author.secondary_emails()
+        );
+    }
+
+    #[test]
+    fn test_set_active() {
+        let mut author = Author::default();
+        author.set_active(ACTIVE);
+        assert!(author.active::<bool>());
+        author.set_active(INACTIVE);
+        assert!(!author.active::<bool>());
+    }
+
+    #[test]
+    fn test_has_alias_ignores_case() {
+        let mut author = Author::new("Tester", "tester@test.com");
+        author.set_alias(Some("JJ"));
+        assert!(author.has_alias("jj"));
+        assert!(!Author::default().has_alias("jj"));
+    }
+
+    #[test]
+    fn test_invalid_aliases() {
+        assert!(!is_valid_alias(""));
+        assert!(!is_valid_alias("+jj"));
+        assert!(!is_valid_alias("a,b"));
+        assert!(!is_valid_alias("jj@test.com"));
+        assert!(!is_valid_alias("All"));
+        assert!(!is_valid_alias("none"));
+    }
+
+    #[test]
+    fn test_ids_are_not_numbers() {
+        assert_eq!(None, format_id(0x1234_5678));
+        assert_eq!(Some("0000000a".to_string()), format_id(0x1_0000_000a));
+        assert!((0..1000).all(|_| generate_id().parse::<u64>().is_err()));
+    }
+
+    #[test]
+    fn test_derive_id_ignores_case() {
+        assert_eq!(
+            derive_id("tester@test.com", 0),
+            derive_id("TESTER@test.com", 0)
+        );
+        assert_ne!(
+            derive_id("tester@test.com", 0),
+            derive_id("tester@test.com", 1)
+        );
+    }
+
     #[test]
     fn test_author_display() {
         let author = Author::new("Tester", "tester@test.com");
@@ -0,0 +1,70 @@
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// What an active co-author does in the current session, which decides the key of their trailer
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::author::role::Role;
+/// assert_eq!(Ok(Role::Navigator), "navigator".parse());
+/// assert_eq!(Ok(Role::Reviewer), "Reviewer".parse());
+/// assert!("observer".parse::<Role>().is_err());
+/// assert_eq!("driver", Role::default().to_string());
+/// ```
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Role {
+    /// Types the code, credited as a co-author
+    #[default]
+    Driver,
+    /// Guides the driver without typing
+    Navigator,
+    /// Reviews the changes as they are made
+    Reviewer,
+}
+
+/// Every role, in the order they are listed
+pub const ROLES: [Role; 3] = [Role::Driver, Role::Navigator, Role::Reviewer];
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ROLES
+            .iter()
+            .find(|role| role.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "'{}' is not a valid role, use driver, navigator or reviewer",
+                    s
+                )
+            })
+    }
+}
+
+impl TryFrom<String> for Role {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Role> for String {
+    fn from(role: Role) -> Self {
+        role.to_string()
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Role::Driver => write!(f, "driver"),
+            Role::Navigator => write!(f, "navigator"),
+            Role::Reviewer => write!(f, "reviewer"),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+/// One comma separated part of an author selection such as `0-2, +jj, -am`
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::author::selection::{SelectionToken, SelectionTarget};
+/// assert_eq!(Ok(SelectionToken::All), "all".parse());
+/// assert_eq!(Ok(SelectionToken::Set(SelectionTarget::Range(0, 3))), "0-3".parse());
+/// assert_eq!(
+///     Ok(SelectionToken::Add(SelectionTarget::Author("jj".to_string()))),
+///     "+jj".parse()
+/// );
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SelectionToken {
+    /// Every author that is not archived
+    All,
+    /// No authors
+    None,
+    /// Authors making up the new selection
+    Set(SelectionTarget),
+    /// Authors added to the current selection
+    Add(SelectionTarget),
+    /// Authors removed from the current selection
+    Remove(SelectionTarget),
+}
+
+/// Authors referred to by a selection token
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SelectionTarget {
+    /// Inclusive range of indexes
+    Range(usize, usize),
+    /// An index, alias or email
+    Author(String),
+}
+
+impl SelectionToken {
+    /// Parses every part of a selection, returning the tokens that parsed and the errors of
+    /// those that did not
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::selection::SelectionToken;
+    /// let (tokens, errors) = SelectionToken::parse_all(&["all", "3-1", "-jj"]);
+    /// assert_eq!(2, tokens.len());
+    /// assert_eq!(1, errors.len());
+    /// ```
+    pub fn parse_all<S: AsRef<str>>(parts: &[S]) -> (Vec<SelectionToken>, Vec<SelectionError>) {
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        for part in parts {
+            match part.as_ref().parse() {
+                Ok(token) => tokens.push(token),
+                Err(e) => errors.push(e),
+            }
+        }
+        (tokens, errors)
+    }
+
+    /// Checks if the token builds a new selection rather than changing the current one
+    pub fn is_absolute(&self) -> bool {
+        match self {
+            SelectionToken::All | SelectionToken::None | SelectionToken::Set(_) => true,
+            SelectionToken::Add(_) | SelectionToken::Remove(_) => false,
+        }
+    }
+}
+
+impl FromStr for SelectionToken {
+    type Err = SelectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("all") {
+            Ok(SelectionToken::All)
+        } else if s.eq_ignore_ascii_case("none") {
+            Ok(SelectionToken::None)
+        } else if let Some(target) = s.strip_prefix('+') {
+            Ok(SelectionToken::Add(target.parse()?))
+        } else if let Some(target) = s.strip_prefix('-') {
+            Ok(SelectionToken::Remove(target.parse()?))
+        } else {
+            Ok(SelectionToken::Set(s.parse()?))
+        }
+    }
+}
+
+impl FromStr for SelectionTarget {
+    type Err = SelectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(SelectionError::Empty);
+        }
+
+        // Only digits on both sides make a range, so that aliases and emails such as `2-fa` or
+        // `3-x@test.com` select an author
+        let is_number =
+            |bound: &str| !bound.is_empty() && bound.chars().all(|c| c.is_ascii_digit());
+        if let Some((start, end)) = s.split_once('-') {
+            let end = end.trim();
+            if is_number(start) && is_number(end) {
+                return match (start.parse::<usize>(), end.parse::<usize>()) {
+                    (Ok(start), Ok(end)) if start <= end => Ok(SelectionTarget::Range(start, end)),
+                    _ => Err(SelectionError::InvalidRange(s.to_string())),
+                };
+            }
+        }
+        Ok(SelectionTarget::Author(s.to_string()))
+    }
+}
+
+/// A part of a selection that could not be understood or applied
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SelectionError {
+    Empty,
+    InvalidRange(String),
+    OutOfRange(String),
+    UnknownAuthor(String),
+}
+
+impl Display for SelectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            SelectionError::Empty => write!(f, "empty selection"),
+            SelectionError::InvalidRange(s) => write!(f, "'{}' is not a valid range", s),
+            SelectionError::OutOfRange(s) => write!(f, "'{}' is out of range", s),
+            SelectionError::UnknownAuthor(s) => write!(f, "'{}' does not match any co-author", s),
+        }
+    }
+}
+
+impl std::error::Error for SelectionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn author(s: &str) -> SelectionTarget {
+        SelectionTarget::Author(s.to_string())
+    }
+
+    #[test]
+    fn test_parse_keywords_ignore_case() {
+        assert_eq!(Ok(SelectionToken::All), " ALL ".parse());
+        assert_eq!(Ok(SelectionToken::None), "None".parse());
+    }
+
+    #[test]
+    fn test_parse_authors() {
+        assert_eq!(Ok(SelectionToken::Set(author("1"))), "1".parse());
+        assert_eq!(
+            Ok(SelectionToken::Set(author("tester@test.com"))),
+            "tester@test.com".parse()
+        );
+        assert_eq!(Ok(SelectionToken::Remove(author("2"))), "-2".parse());
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        assert_eq!(
+            Ok(SelectionToken::Add(SelectionTarget::Range(1, 2))),
+            "+1-2".parse()
+        );
+        assert_eq!(
+            Err(SelectionError::InvalidRange("3-1".to_string())),
+            "3-1".parse::<SelectionToken>()
+        );
+    }
+
+    #[test]
+    fn test_parse_alias_starting_with_digits_is_not_range() {
+        assert_eq!(Ok(SelectionToken::Set(author("2-fa"))), "2-fa".parse());
+        assert_eq!(Ok(SelectionToken::Add(author("1-x"))), "+1-x".parse());
+    }
+
+    #[test]
+    fn test_parse_email_starting_with_digits_is_not_range() {
+        assert_eq!(
+            Ok(SelectionToken::Set(author("3-x@x.com"))),
+            "3-x@x.com".parse()
+        );
+    }
+
+    #[test]
+    fn test_parse_email_with_dash_is_not_range() {
+        assert_eq!(
+            Ok(SelectionToken::Set(author("jo-ann@test.com"))),
+            "jo-ann@test.com".parse()
+        );
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        assert_eq!(Err(SelectionError::Empty), "".parse::<SelectionToken>());
+        assert_eq!(Err(SelectionError::Empty), "+".parse::<SelectionToken>());
+    }
+
+    #[test]
+    fn test_is_absolute() {
+        assert!(SelectionToken::All.is_absolute());
+        assert!(!SelectionToken::Add(author("jj")).is_absolute());
+    }
+}
@@ -0,0 +1,159 @@
+use crate::models::author::{normalize_email, normalize_name, Author, COAUTHOR_TRAILER_KEY};
+
+/// Format of the value of a co-author trailer, as used by git hosts to match an account
+pub const DEFAULT_TRAILER_FORMAT: &str = "{name} <{email}>";
+
+const PLACEHOLDERS: [&str; 3] = ["name", "email", "alias"];
+
+/// A trailer key and the format of its value, where `{name}`, `{email}` and `{alias}` stand for
+/// the details of the author
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::author::Author;
+/// # use pair_commit_tool::models::author::trailer::TrailerTemplate;
+/// let mut author = Author::new("Tester", "tester@test.com");
+/// author.set_alias(Some("tt"));
+/// let template = TrailerTemplate::new("Mob", "{alias} ({email})");
+/// assert_eq!("Mob: tt (tester@test.com)", template.render(&author));
+/// assert_eq!(
+///     "Co-authored-by: Tester <tester@test.com>",
+///     TrailerTemplate::default().render(&author)
+/// );
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TrailerTemplate {
+    pub key: String,
+    pub format: String,
+}
+
+impl TrailerTemplate {
+    pub fn new<K: AsRef<str>, F: AsRef<str>>(key: K, format: F) -> TrailerTemplate {
+        TrailerTemplate {
+            key: key.as_ref().to_string(),
+            format: format.as_ref().to_string(),
+        }
+    }
+
+    /// A single line trailer crediting the author
+    ///
+    /// Names and emails loaded from files written by hand are normalised as well, and lose any
+    /// `<` or `>`, so that the trailer always reads back as one trailer with one email. An author
+    /// without an alias is credited by name in place of `{alias}`.
+    pub fn render(&self, author: &Author) -> String {
+        let name: String = normalize_name(author.name())
+            .chars()
+            .filter(|&c| c != '<' && c != '>')
+            .collect();
+        let email: String = normalize_email(author.email())
+            .chars()
+            .filter(|&c| c != '<' && c != '>' && !c.is_whitespace())
+            .collect();
+        let alias = match author.alias() {
+            Some(alias) => normalize_name(alias),
+            None => name.clone(),
+        };
+        let value = parse_format(&self.format)
+            .into_iter()
+            .map(|part| match part {
+                FormatPart::Placeholder("name") => name.as_str(),
+                FormatPart::Placeholder("email") => email.as_str(),
+                FormatPart::Placeholder("alias") => alias.as_str(),
+                FormatPart::Placeholder(other) | FormatPart::Text(other) => other,
+            })
+            .collect::<String>();
+        format!("{}: {}", self.key, normalize_name(&value))
+    }
+}
+
+impl Default for TrailerTemplate {
+    fn default() -> Self {
+        TrailerTemplate::new(COAUTHOR_TRAILER_KEY, DEFAULT_TRAILER_FORMAT)
+    }
+}
+
+/// Checks that a trailer format fits on one line, refers to the author with at least one
+/// placeholder and has no placeholders other than `{name}`, `{email}` and `{alias}`
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::author::trailer::is_valid_trailer_format;
+/// assert!(is_valid_trailer_format("{name} <{email}>"));
+/// assert!(is_valid_trailer_format("{alias}"));
+/// assert!(!is_valid_trailer_format("Tester"));
+/// assert!(!is_valid_trailer_format("{nickname}"));
+/// assert!(!is_valid_trailer_format("{name"));
+/// assert!(!is_valid_trailer_format("{name}\n{email}"));
+/// ```
+pub fn is_valid_trailer_format(format: &str) -> bool {
+    let parts = parse_format(format);
+    !format.chars().any(char::is_control)
+        && parts
+            .iter()
+            .any(|part| matches!(part, FormatPart::Placeholder(_)))
+        && parts.iter().all(|part| match part {
+            FormatPart::Placeholder(name) => PLACEHOLDERS.contains(name),
+            FormatPart::Text(text) => !text.contains(['{', '}']),
+        })
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum FormatPart<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+/// Splits a format into text and placeholders in a single pass, so that a name containing
+/// `{email}` is not replaced in turn
+fn parse_format(format: &str) -> Vec<FormatPart<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        let length = match rest[start..].find('}') {
+            Some(length) => length,
+            None => break,
+        };
+        if start > 0 {
+            parts.push(FormatPart::Text(&rest[..start]));
+        }
+        parts.push(FormatPart::Placeholder(&rest[start + 1..start + length]));
+        rest = &rest[start + length + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(FormatPart::Text(rest));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            vec![
+                FormatPart::Placeholder("name"),
+                FormatPart::Text(" <"),
+                FormatPart::Placeholder("email"),
+                FormatPart::Text(">"),
+            ],
+            parse_format(DEFAULT_TRAILER_FORMAT)
+        );
+        assert_eq!(vec![FormatPart::Text("{name")], parse_format("{name"));
+    }
+
+    #[test]
+    fn test_placeholders_are_not_replaced_twice() {
+        let author = Author::new("{email}", "tester@test.com");
+        let template = TrailerTemplate::new("Pair", "{name} {alias}");
+        assert_eq!("Pair: {email} {email}", template.render(&author));
+    }
+
+    #[test]
+    fn test_empty_name_leaves_a_single_line() {
+        let author = Author::new("", "tester@test.com");
+        assert_eq!(
+            "Co-authored-by: <tester@test.com>",
+            TrailerTemplate::default().render(&author)
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+
+/// Co-authors bound to a branch name or pattern
+///
+/// Patterns can use `*` to match any run of characters, including `/`, and `?` to match a single
+/// character. Members are stored by author reference, like the members of a team.
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::branch_pair::BranchPair;
+/// let pair = BranchPair::new("feature/*", vec!["0a1b2c3d".to_string()]);
+/// assert!(pair.matches("feature/login"));
+/// assert!(!pair.matches("main"));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BranchPair {
+    branch: String,
+    #[serde(default)]
+    members: Vec<String>,
+}
+
+impl BranchPair {
+    pub fn new<S: AsRef<str>>(branch: S, members: Vec<String>) -> BranchPair {
+        BranchPair {
+            branch: branch.as_ref().to_string(),
+            members,
+        }
+    }
+
+    /// Branch name or pattern the co-authors are bound to
+    pub fn branch(&self) -> &String {
+        &self.branch
+    }
+
+    pub fn members(&self) -> &Vec<String> {
+        &self.members
+    }
+
+    pub fn is_pattern(&self) -> bool {
+        self.branch.contains(['*', '?'])
+    }
+
+    pub fn matches(&self, branch: &str) -> bool {
+        let pattern: Vec<char> = self.branch.chars().collect();
+        let branch: Vec<char> = branch.chars().collect();
+        glob_matches(&pattern, &branch)
+    }
+}
+
+/// Matches `*` and `?` wildcards, backtracking only to the last `*` seen so that patterns with
+/// many stars stay linear in practice
+fn glob_matches(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // The position after the last `*` and the text position it is currently matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            star = Some((p, t));
+        } else if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p;
+            t = star_t + 1;
+            star = Some((star_p, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Every branch that has co-authors bound to it
+#[derive(Debug, Default)]
+pub struct BranchPairCollection {
+    pairs: Vec<BranchPair>,
+}
+
+impl BranchPairCollection {
+    pub fn new() -> BranchPairCollection {
+        BranchPairCollection {
+            ..BranchPairCollection::default()
+        }
+    }
+
+    pub fn pairs(&self) -> &Vec<BranchPair> {
+        &self.pairs
+    }
+
+    /// Binds the co-authors to the branch, replacing any co-authors already bound to it
+    pub fn set(&mut self, pair: BranchPair) {
+        match self.pairs.iter_mut().find(|p| p.branch == pair.branch) {
+            Some(existing) => *existing = pair,
+            None => self.pairs.push(pair),
+        }
+    }
+
+    /// Removes the co-authors bound to the branch name or pattern
+    pub fn clear(&mut self, branch: &str) -> Option<BranchPair> {
+        let index = self.pairs.iter().position(|p| p.branch == branch)?;
+        Some(self.pairs.remove(index))
+    }
+
+    /// Makes members referring to one author refer to another, such as when the authors are
+    /// merged, returning true if any binding changed
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::branch_pair::{BranchPair, BranchPairCollection};
+    /// let mut pairs = BranchPairCollection::new();
+    /// pairs.set(BranchPair::new("main", vec!["a".to_string(), "b".to_string()]));
+    /// assert!(pairs.replace_reference("b", "a"));
+    /// assert_eq!(&vec!["a".to_string()], pairs.pairs()[0].members());
+    /// ```
+    pub fn replace_reference(&mut self, from: &str, to: &str) -> bool {
+        let mut changed = false;
+        for pair in self.pairs.iter_mut() {
+            if from.is_empty() || !pair.members.iter().any(|m| m.eq_ignore_ascii_case(from)) {
+                continue;
+            }
+            pair.members.retain(|m| !m.eq_ignore_ascii_case(from));
+            if !pair.members.iter().any(|m| m.eq_ignore_ascii_case(to)) {
+                pair.members.push(to.to_string());
+            }
+            changed = true;
+        }
+        changed
+    }
+
+    /// Finds the co-authors of a branch
+    ///
+    /// A binding to the exact branch name is preferred, after which patterns are tried in the
+    /// order they were set.
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::branch_pair::{BranchPair, BranchPairCollection};
+    /// let mut pairs = BranchPairCollection::new();
+    /// pairs.set(BranchPair::new("feature/*", vec!["a".to_string()]));
+    /// pairs.set(BranchPair::new("feature/login", vec!["b".to_string()]));
+    /// assert_eq!("feature/login", pairs.find_for("feature/login").unwrap().branch());
+    /// assert_eq!("feature/*", pairs.find_for("feature/logout").unwrap().branch());
+    /// assert!(pairs.find_for("main").is_none());
+    /// ```
+    pub fn find_for(&self, branch: &str) -> Option<&BranchPair> {
+        self.pairs
+            .iter()
+            .find(|p| !p.is_pattern() && p.branch == branch)
+            .or_else(|| {
+                self.pairs
+                    .iter()
+                    .find(|p| p.is_pattern() && p.matches(branch))
+            })
+    }
+}
+
+impl From<Vec<BranchPair>> for BranchPairCollection {
+    fn from(pairs: Vec<BranchPair>) -> Self {
+        BranchPairCollection { pairs }
+    }
+}
+
+impl AsRef<BranchPairCollection> for BranchPairCollection {
+    fn as_ref(&self) -> &BranchPairCollection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(branch: &str) -> BranchPair {
+        BranchPair::new(branch, Vec::new())
+    }
+
+    #[test]
+    fn test_glob_patterns() {
+        assert!(pair("*").matches("feature/nested/branch"));
+        assert!(pair("release-?.x").matches("release-2.x"));
+        assert!(!pair("release-?.x").matches("release-10.x"));
+        assert!(pair("main").matches("main"));
+        assert!(!pair("main").matches("main2"));
+    }
+
+    #[test]
+    fn test_glob_backtracks_to_last_star() {
+        assert!(pair("feature/*-*").matches("feature/a-b-c"));
+        assert!(pair("*/fix-*").matches("team/a/fix-1"));
+        assert!(!pair("*/fix-*").matches("team/a/feat-1"));
+        assert!(pair("a**b").matches("ab"));
+        assert!(!pair("*a").matches(""));
+    }
+
+    #[test]
+    fn test_glob_with_many_stars_is_fast() {
+        let pattern = "*a".repeat(30) + "b";
+        assert!(!pair(&pattern).matches(&"a".repeat(100)));
+    }
+
+    #[test]
+    fn test_set_replaces_existing() {
+        let mut pairs = BranchPairCollection::new();
+        pairs.set(BranchPair::new("main", vec!["a".to_string()]));
+        pairs.set(BranchPair::new("main", vec!["b".to_string()]));
+        assert_eq!(1, pairs.pairs().len());
+        assert_eq!(&vec!["b".to_string()], pairs.pairs()[0].members());
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut pairs = BranchPairCollection::from(vec![pair("main")]);
+        assert!(pairs.clear("other").is_none());
+        assert!(pairs.clear("main").is_some());
+        assert!(pairs.pairs().is_empty());
+    }
+}
@@ -0,0 +1,451 @@
+use std::fmt::{Display, Error, Formatter};
+
+/// Comment character git uses unless `core.commentChar` is set
+pub const DEFAULT_COMMENT_CHAR: &str = "#";
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// A commit message as written by git to the message file of the `prepare-commit-msg` hook
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::commit_message::CommitMessage;
+/// let mut message = CommitMessage::from("Subject\n");
+/// message.add_trailers(&["Co-authored-by: Tester <tester@test.com>"]);
+/// assert_eq!("Subject\n\nCo-authored-by: Tester <tester@test.com>\n", message.to_string());
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CommitMessage {
+    lines: Vec<String>,
+    comment_char: String,
+}
+
+impl CommitMessage {
+    /// Uses the comment character set with git's `core.commentChar` to tell comments apart
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::commit_message::CommitMessage;
+    /// let mut message = CommitMessage::from("Subject\n\n; comment\n").with_comment_char(";");
+    /// message.add_trailers(&["Co-authored-by: Tester <tester@test.com>"]);
+    /// assert_eq!(
+    ///     "Subject\n\nCo-authored-by: Tester <tester@test.com>\n\n; comment\n",
+    ///     message.to_string()
+    /// );
+    /// ```
+    pub fn with_comment_char<S: AsRef<str>>(mut self, comment_char: S) -> Self {
+        self.comment_char = comment_char.as_ref().to_string();
+        self
+    }
+
+    /// Adds trailers to the trailer block of the message, skipping any that are already present
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::commit_message::CommitMessage;
+    /// let trailer = "Co-authored-by: Tester <tester@test.com>";
+    /// let mut message = CommitMessage::from("Subject\n");
+    /// message.add_trailers(&[trailer]);
+    /// message.add_trailers(&[trailer]);
+    /// assert_eq!(vec![trailer], message.trailers());
+    /// ```
+    pub fn add_trailers<S: AsRef<str>>(&mut self, trailers: &[S]) {
+        let mut existing: Vec<String> = self.trailers().iter().map(|t| normalise(t)).collect();
+        let mut new_trailers: Vec<String> = Vec::new();
+        for trailer in trailers.iter().map(|t| t.as_ref().trim()) {
+            if trailer.is_empty() || existing.contains(&normalise(trailer)) {
+                continue;
+            }
+            existing.push(normalise(trailer));
+            new_trailers.push(trailer.to_string());
+        }
+        if new_trailers.is_empty() {
+            return;
+        }
+
+        match (self.last_content_line(), self.trailer_block_start()) {
+            (Some(last), Some(_start)) => self.insert_lines(last + 1, new_trailers),
+            (Some(last), None) => {
+                new_trailers.insert(0, String::new());
+                self.insert_lines(last + 1, new_trailers)
+            }
+            (None, _) => {
+                // Leave an empty first line for the subject and a blank line below it
+                new_trailers.insert(0, String::new());
+                new_trailers.insert(0, String::new());
+                self.insert_lines(0, new_trailers)
+            }
+        }
+    }
+
+    /// Removes every trailer with the given key from the trailer block of the message
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::commit_message::CommitMessage;
+    /// let mut message = CommitMessage::from(
+    ///     "Subject\n\nCo-authored-by: Tester <tester@test.com>\nSigned-off-by: Other <other@test.com>\n",
+    /// );
+    /// message.remove_trailers("co-authored-by");
+    /// assert_eq!(vec!["Signed-off-by: Other <other@test.com>"], message.trailers());
+    /// ```
+    pub fn remove_trailers(&mut self, key: &str) {
+        let (start, end) = match (self.trailer_block_start(), self.last_content_line()) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return,
+        };
+
+        let mut kept: Vec<String> = Vec::new();
+        let mut removing = false;
+        for line in &self.lines[start..=end] {
+            if is_trailer(line) {
+                removing = trailer_key(line).eq_ignore_ascii_case(key.trim());
+            } else if self.is_comment(line) || !line.starts_with(char::is_whitespace) {
+                removing = false;
+            }
+            if !removing {
+                kept.push(line.clone());
+            }
+        }
+
+        if kept.iter().all(|line| self.is_comment(line)) {
+            // Drop the blank line that separated the now empty trailer block
+            self.lines.splice(start - 1..=end, kept);
+        } else {
+            self.lines.splice(start..=end, kept);
+        }
+    }
+
+    /// Returns the lines of the trailer block at the end of the message
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::commit_message::CommitMessage;
+    /// let message = CommitMessage::from("Subject\n\nSigned-off-by: Tester <tester@test.com>\n");
+    /// assert_eq!(vec!["Signed-off-by: Tester <tester@test.com>"], message.trailers());
+    /// ```
+    pub fn trailers(&self) -> Vec<&str> {
+        match (self.trailer_block_start(), self.last_content_line()) {
+            (Some(start), Some(end)) => self.lines[start..=end]
+                .iter()
+                .filter(|line| !self.is_comment(line))
+                .map(|line| line.as_str())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn is_comment(&self, line: &str) -> bool {
+        line.starts_with(&self.comment_char)
+    }
+
+    fn insert_lines(&mut self, index: usize, lines: Vec<String>) {
+        self.lines.splice(index..index, lines);
+    }
+
+    /// Index of the first line that is not part of the editable message
+    fn content_end(&self) -> usize {
+        self.lines
+            .iter()
+            .position(|line| {
+                line.strip_prefix(&self.comment_char)
+                    .is_some_and(|rest| rest.trim() == SCISSORS)
+            })
+            .unwrap_or(self.lines.len())
+    }
+
+    fn last_content_line(&self) -> Option<usize> {
+        self.lines[..self.content_end()]
+            .iter()
+            .rposition(|line| !line.trim().is_empty() && !self.is_comment(line))
+    }
+
+    fn trailer_block_start(&self) -> Option<usize> {
+        let last = self.last_content_line()?;
+        let start = self.lines[..=last]
+            .iter()
+            .rposition(|line| line.trim().is_empty())
+            .map_or(0, |blank| blank + 1);
+        let first_content_line = self
+            .lines
+            .iter()
+            .position(|line| !line.trim().is_empty() && !self.is_comment(line))?;
+        if start <= first_content_line {
+            // The subject paragraph is never a trailer block
+            return None;
+        }
+
+        let paragraph = self.lines[start..=last]
+            .iter()
+            .filter(|line| !self.is_comment(line))
+            .collect::<Vec<&String>>();
+        let is_trailer_block = paragraph
+            .iter()
+            .enumerate()
+            .all(|(i, line)| is_trailer(line) || (i > 0 && line.starts_with(char::is_whitespace)));
+        if is_trailer_block {
+            Some(start)
+        } else {
+            None
+        }
+    }
+}
+
+/// Where the commit message came from, as passed by git to the `prepare-commit-msg` hook
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::commit_message::CommitSource;
+/// assert_eq!(CommitSource::Merge, CommitSource::from("merge"));
+/// assert_eq!(CommitSource::Editor, CommitSource::from(""));
+/// assert_eq!(CommitSource::Amend, CommitSource::from("commit").reusing_head(true));
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CommitSource {
+    /// No message was given, so the user writes it in the editor
+    Editor,
+    /// `-m` or `-F`
+    Message,
+    /// `-t` or `commit.template`
+    Template,
+    /// A merge commit or `.git/MERGE_MSG`
+    Merge,
+    /// `.git/SQUASH_MSG`
+    Squash,
+    /// `-c` or `-C` with a commit other than `HEAD`
+    Commit,
+    /// `--amend`, which reuses the message of `HEAD`
+    Amend,
+}
+
+impl CommitSource {
+    /// Git passes `--amend` and `-c`/`-C` as the same `commit` source, told apart by whether the
+    /// reused commit is `HEAD`
+    pub fn reusing_head(self, is_head: bool) -> CommitSource {
+        match self {
+            CommitSource::Commit if is_head => CommitSource::Amend,
+            source => source,
+        }
+    }
+}
+
+impl<S: AsRef<str>> From<S> for CommitSource {
+    fn from(s: S) -> Self {
+        match s.as_ref() {
+            "message" => CommitSource::Message,
+            "template" => CommitSource::Template,
+            "merge" => CommitSource::Merge,
+            "squash" => CommitSource::Squash,
+            "commit" => CommitSource::Commit,
+            _ => CommitSource::Editor,
+        }
+    }
+}
+
+impl<S: AsRef<str>> From<S> for CommitMessage {
+    fn from(s: S) -> Self {
+        CommitMessage {
+            lines: s.as_ref().split('\n').map(String::from).collect(),
+            comment_char: DEFAULT_COMMENT_CHAR.to_string(),
+        }
+    }
+}
+
+impl Display for CommitMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.lines.join("\n"))
+    }
+}
+
+/// Checks if a line has the `Token: value` form of a git trailer
+pub fn is_trailer(line: &str) -> bool {
+    match line.split_once(':') {
+        Some((key, value)) => is_valid_trailer_key(key) && !value.trim().is_empty(),
+        None => false,
+    }
+}
+
+/// Checks that a trailer key is a token of ASCII letters, digits and `-`
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::commit_message::is_valid_trailer_key;
+/// assert!(is_valid_trailer_key("Co-authored-by"));
+/// assert!(!is_valid_trailer_key("Paired with"));
+/// assert!(!is_valid_trailer_key("Co-authored-by:"));
+/// ```
+pub fn is_valid_trailer_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn trailer_key(trailer: &str) -> &str {
+    trailer.split(':').next().unwrap_or("").trim()
+}
+
+fn normalise(trailer: &str) -> String {
+    match trailer.find(':') {
+        Some(index) => format!(
+            "{}: {}",
+            trailer[..index].trim().to_lowercase(),
+            trailer[index + 1..].trim().to_lowercase()
+        ),
+        None => trailer.trim().to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRAILER: &str = "Co-authored-by: Tester <tester@test.com>";
+    const SCISSORS_LINE: &str = "# ------------------------ >8 ------------------------";
+
+    fn add_trailer(message: &str) -> String {
+        let mut message = CommitMessage::from(message);
+        message.add_trailers(&[TRAILER]);
+        message.to_string()
+    }
+
+    #[test]
+    fn test_add_trailers_to_empty_message() {
+        assert_eq!(format!("\n\n{}\n", TRAILER), add_trailer(""));
+    }
+
+    #[test]
+    fn test_add_trailers_before_comments() {
+        let message = "\n# Please enter the commit message for your changes.\n#\n";
+        let expected = format!(
+            "\n\n{}\n\n# Please enter the commit message for your changes.\n#\n",
+            TRAILER
+        );
+        assert_eq!(expected, add_trailer(message));
+    }
+
+    #[test]
+    fn test_add_trailers_after_subject() {
+        let message = "Subject\n\n# Please enter the commit message\n";
+        let expected = format!(
+            "Subject\n\n{}\n\n# Please enter the commit message\n",
+            TRAILER
+        );
+        assert_eq!(expected, add_trailer(message));
+    }
+
+    #[test]
+    fn test_add_trailers_to_existing_trailer_block() {
+        let message = "Subject\n\nBody\n\nSigned-off-by: Other <other@test.com>\n";
+        let expected = format!(
+            "Subject\n\nBody\n\nSigned-off-by: Other <other@test.com>\n{}\n",
+            TRAILER
+        );
+        assert_eq!(expected, add_trailer(message));
+    }
+
+    #[test]
+    fn test_add_trailers_before_scissors_line() {
+        let message = format!("Subject\n\n{}\ndiff --git a/file b/file\n", SCISSORS_LINE);
+        let expected = format!(
+            "Subject\n\n{}\n\n{}\ndiff --git a/file b/file\n",
+            TRAILER, SCISSORS_LINE
+        );
+        assert_eq!(expected, add_trailer(&message));
+    }
+
+    #[test]
+    fn test_comment_char() {
+        let content = "Subject\n\n# Heading\n\n; comment\n\
+                       ; ------------------------ >8 ------------------------\n\
+                       diff --git a/file b/file\n";
+        let mut message = CommitMessage::from(content).with_comment_char(";");
+        message.add_trailers(&[TRAILER]);
+        let expected = format!(
+            "Subject\n\n# Heading\n\n{}\n\n; comment\n\
+             ; ------------------------ >8 ------------------------\n\
+             diff --git a/file b/file\n",
+            TRAILER
+        );
+        assert_eq!(expected, message.to_string());
+    }
+
+    #[test]
+    fn test_add_trailers_subject_is_not_trailer_block() {
+        let message = "Fix: handle empty input\n";
+        let expected = format!("Fix: handle empty input\n\n{}\n", TRAILER);
+        assert_eq!(expected, add_trailer(message));
+    }
+
+    #[test]
+    fn test_add_trailers_is_idempotent() {
+        let once = add_trailer("Subject\n");
+        assert_eq!(once, add_trailer(&once));
+    }
+
+    #[test]
+    fn test_add_trailers_ignores_case_of_existing_trailer() {
+        let message = "Subject\n\nco-authored-by: tester <TESTER@test.com>\n";
+        assert_eq!(message, add_trailer(message));
+    }
+
+    #[test]
+    fn test_add_trailers_skips_duplicates_in_input() {
+        let mut message = CommitMessage::from("Subject\n");
+        message.add_trailers(&[TRAILER, TRAILER]);
+        assert_eq!(vec![TRAILER], message.trailers());
+    }
+
+    #[test]
+    fn test_add_no_trailers_leaves_message_unchanged() {
+        let mut message = CommitMessage::from("Subject\n");
+        message.add_trailers::<&str>(&[]);
+        assert_eq!("Subject\n", message.to_string());
+    }
+
+    #[test]
+    fn test_trailers_not_in_body() {
+        let message = CommitMessage::from("Subject\n\nNote: this is body text\nand more\n");
+        assert!(message.trailers().is_empty());
+    }
+
+    #[test]
+    fn test_remove_trailers_keeps_other_trailers() {
+        let mut message = CommitMessage::from(format!(
+            "Subject\n\n{}\nSigned-off-by: Other <other@test.com>\n",
+            TRAILER
+        ));
+        message.remove_trailers("Co-authored-by");
+        assert_eq!(
+            "Subject\n\nSigned-off-by: Other <other@test.com>\n",
+            message.to_string()
+        );
+    }
+
+    #[test]
+    fn test_remove_trailers_removes_empty_block() {
+        let mut message = CommitMessage::from(format!("Subject\n\n{}\n\n# comment\n", TRAILER));
+        message.remove_trailers("Co-authored-by");
+        assert_eq!("Subject\n\n# comment\n", message.to_string());
+    }
+
+    #[test]
+    fn test_remove_trailers_without_trailer_block() {
+        let mut message = CommitMessage::from("Subject\n\nBody\n");
+        message.remove_trailers("Co-authored-by");
+        assert_eq!("Subject\n\nBody\n", message.to_string());
+    }
+
+    #[test]
+    fn test_commit_source_from_unknown() {
+        assert_eq!(CommitSource::Editor, CommitSource::from("unknown"));
+    }
+
+    #[test]
+    fn test_reusing_head() {
+        assert_eq!(
+            CommitSource::Commit,
+            CommitSource::from("commit").reusing_head(false)
+        );
+        assert_eq!(
+            CommitSource::Message,
+            CommitSource::from("message").reusing_head(true)
+        );
+    }
+
+    #[test]
+    fn test_is_trailer() {
+        assert!(is_trailer(TRAILER));
+        assert!(!is_trailer("Just a sentence: with a colon"));
+        assert!(!is_trailer("Key:"));
+    }
+}
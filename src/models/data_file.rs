@@ -0,0 +1,159 @@
+use std::fmt::{Display, Error, Formatter};
+
+use serde_yaml::{Mapping, Value};
+
+/// Version of the authors file written by this version of the tool
+pub const DATA_VERSION: u64 = 1;
+
+const VERSION_KEY: &str = "version";
+const AUTHORS_KEY: &str = "authors";
+
+/// Upgrades a document from one version to the next
+type Migration = fn(Value) -> Value;
+
+/// Migrations from each version to the next, starting at version 0
+const MIGRATIONS: [Migration; DATA_VERSION as usize] = [from_bare_list];
+
+/// Why an authors file cannot be used
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DataFileError {
+    /// The file was written by a newer version of the tool
+    Newer(u64),
+    /// The file is neither a list of authors nor a document with a version
+    Invalid(String),
+}
+
+impl Display for DataFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            DataFileError::Newer(version) => write!(
+                f,
+                "it was written by a newer version of pair-commit-tool (data version {}, this \
+                 version reads up to {}), upgrade pair-commit-tool to use it",
+                version, DATA_VERSION
+            ),
+            DataFileError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Version of a document, where the bare list of authors written before the file had a version
+/// is version 0
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::data_file::document_version;
+/// let legacy = serde_yaml::from_str("- name: Tester\n  email: tester@test.com").unwrap();
+/// assert_eq!(Ok(0), document_version(&legacy));
+/// let current = serde_yaml::from_str("version: 1\nauthors: []").unwrap();
+/// assert_eq!(Ok(1), document_version(&current));
+/// ```
+pub fn document_version(document: &Value) -> Result<u64, DataFileError> {
+    match document {
+        Value::Null | Value::Sequence(_) => Ok(0),
+        Value::Mapping(mapping) => mapping
+            .get(&Value::from(VERSION_KEY))
+            .and_then(Value::as_u64)
+            .ok_or_else(|| {
+                DataFileError::Invalid(String::from("expected a version number in 'version'"))
+            }),
+        _ => Err(DataFileError::Invalid(String::from(
+            "expected a list of co-authors or a document with a version",
+        ))),
+    }
+}
+
+/// Upgrades a document written by any earlier version of the tool to the current version,
+/// keeping the fields it does not know about as they are
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::data_file::{upgrade, DataFileError, DATA_VERSION};
+/// let legacy = serde_yaml::from_str("- name: Tester\n  email: tester@test.com").unwrap();
+/// let document = upgrade(legacy).unwrap();
+/// assert_eq!(Some(DATA_VERSION), document["version"].as_u64());
+/// assert_eq!(Some("Tester"), document["authors"][0]["name"].as_str());
+///
+/// let newer = serde_yaml::from_str("version: 99\nauthors: []").unwrap();
+/// assert_eq!(Err(DataFileError::Newer(99)), upgrade(newer));
+/// ```
+pub fn upgrade(document: Value) -> Result<Value, DataFileError> {
+    let version = document_version(&document)?;
+    if version > DATA_VERSION {
+        return Err(DataFileError::Newer(version));
+    }
+    Ok(MIGRATIONS[version as usize..]
+        .iter()
+        .fold(document, |document, migration| migration(document)))
+}
+
+/// The authors of an upgraded document
+pub fn authors_value(document: &Value) -> Value {
+    document
+        .get(AUTHORS_KEY)
+        .cloned()
+        .unwrap_or_else(|| Value::Sequence(Vec::new()))
+}
+
+/// Replaces the authors of an upgraded document, keeping its other fields in their place
+pub fn set_authors_value(document: &mut Value, authors: Value) {
+    if let Value::Mapping(mapping) = document {
+        set_field(mapping, VERSION_KEY, Value::from(DATA_VERSION));
+        set_field(mapping, AUTHORS_KEY, authors);
+    }
+}
+
+/// Updates a field where it is, as inserting an existing key moves it to the end
+fn set_field(mapping: &mut Mapping, key: &str, value: Value) {
+    match mapping.get_mut(&Value::from(key)) {
+        Some(field) => *field = value,
+        None => {
+            mapping.insert(Value::from(key), value);
+        }
+    }
+}
+
+/// A new document without any authors
+pub fn empty_document() -> Value {
+    from_bare_list(Value::Null)
+}
+
+/// Version 0 was a bare list of authors
+fn from_bare_list(document: Value) -> Value {
+    let authors = match document {
+        Value::Null => Value::Sequence(Vec::new()),
+        authors => authors,
+    };
+    let mut mapping = Mapping::new();
+    mapping.insert(Value::from(VERSION_KEY), Value::from(1u64));
+    mapping.insert(Value::from(AUTHORS_KEY), authors);
+    Value::Mapping(mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_keeps_unknown_fields() {
+        let document: Value =
+            serde_yaml::from_str("version: 1\nauthors: []\nteams:\n  - name: core").unwrap();
+        let mut upgraded = upgrade(document.clone()).unwrap();
+        assert_eq!(document, upgraded);
+
+        set_authors_value(&mut upgraded, Value::Sequence(Vec::new()));
+        assert_eq!(Some("core"), upgraded["teams"][0]["name"].as_str());
+    }
+
+    #[test]
+    fn test_empty_document() {
+        let document = upgrade(Value::Null).unwrap();
+        assert_eq!(empty_document(), document);
+        assert_eq!(Value::Sequence(Vec::new()), authors_value(&document));
+    }
+
+    #[test]
+    fn test_invalid_document() {
+        let document = serde_yaml::from_str("authors: []").unwrap();
+        assert!(matches!(upgrade(document), Err(DataFileError::Invalid(_))));
+        assert!(document_version(&Value::from("authors")).is_err());
+    }
+}
@@ -1 +1,9 @@
 pub mod author;
+pub mod branch_pair;
+pub mod commit_message;
+pub mod data_file;
+pub mod profile;
+pub mod project;
+pub mod session;
+pub mod settings;
+pub mod team;
@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// A named profile with its own co-authors and settings, and the directories it is used in
+/// # Examples
+/// ```
+/// # use std::path::Path;
+/// # use pair_commit_tool::models::profile::Profile;
+/// let mut profile = Profile::new("work");
+/// profile.add_directory("~/work");
+/// let home = Path::new("/home/tester");
+/// assert!(profile.directory_for(Path::new("/home/tester/work/project"), home).is_some());
+/// assert!(profile.directory_for(Path::new("/home/tester/workshop"), home).is_none());
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    directories: Vec<String>,
+}
+
+impl Profile {
+    pub fn new<S: AsRef<str>>(name: S) -> Profile {
+        Profile {
+            name: name.as_ref().to_string(),
+            directories: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Directories the profile is used in, where `~` stands for the home directory
+    pub fn directories(&self) -> &Vec<String> {
+        &self.directories
+    }
+
+    /// Adds a directory, returning false if the profile already had it
+    pub fn add_directory<S: AsRef<str>>(&mut self, directory: S) -> bool {
+        let directory = directory.as_ref().to_string();
+        if self.directories.contains(&directory) {
+            false
+        } else {
+            self.directories.push(directory);
+            true
+        }
+    }
+
+    /// Removes a directory, returning false if the profile did not have it
+    pub fn remove_directory(&mut self, directory: &str) -> bool {
+        let len = self.directories.len();
+        self.directories.retain(|d| d != directory);
+        self.directories.len() != len
+    }
+
+    /// The directory of the profile containing `dir`, if there is one, with the deepest directory
+    /// taking precedence
+    pub fn directory_for(&self, dir: &Path, home: &Path) -> Option<PathBuf> {
+        self.directories
+            .iter()
+            .map(|directory| expand_home(directory, home))
+            .filter(|directory| dir.starts_with(directory))
+            .max_by_key(|directory| directory.components().count())
+    }
+}
+
+fn expand_home(directory: &str, home: &Path) -> PathBuf {
+    if directory == "~" {
+        home.to_path_buf()
+    } else if let Some(relative) = directory.strip_prefix("~/") {
+        home.join(relative)
+    } else {
+        PathBuf::from(directory)
+    }
+}
+
+/// Checks that a profile name can be used as a directory name
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::profile::is_valid_profile_name;
+/// assert!(is_valid_profile_name("open-source"));
+/// assert!(!is_valid_profile_name("../work"));
+/// assert!(!is_valid_profile_name(".."));
+/// assert!(!is_valid_profile_name(""));
+/// ```
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && !name
+            .chars()
+            .any(|c| c == '/' || c == '\\' || c.is_whitespace() || c.is_control())
+}
+
+/// Every profile with directory rules
+#[derive(Debug, Default)]
+pub struct ProfileCollection {
+    profiles: Vec<Profile>,
+}
+
+impl ProfileCollection {
+    pub fn new() -> ProfileCollection {
+        ProfileCollection {
+            ..ProfileCollection::default()
+        }
+    }
+
+    pub fn profiles(&self) -> &Vec<Profile> {
+        &self.profiles
+    }
+
+    pub fn find_profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// Finds a profile, adding it if it does not exist yet
+    pub fn find_or_add_profile(&mut self, name: &str) -> &mut Profile {
+        let index = match self.profiles.iter().position(|p| p.name == name) {
+            Some(index) => index,
+            None => {
+                self.profiles.push(Profile::new(name));
+                self.profiles.len() - 1
+            }
+        };
+        &mut self.profiles[index]
+    }
+
+    /// Removes profiles that are left without directories
+    pub fn remove_empty(&mut self) {
+        self.profiles.retain(|p| !p.directories.is_empty());
+    }
+
+    /// The profile used in `dir` with the directory that selects it, where the deepest directory
+    /// of any profile takes precedence
+    /// # Examples
+    /// ```
+    /// # use std::path::{Path, PathBuf};
+    /// # use pair_commit_tool::models::profile::{Profile, ProfileCollection};
+    /// let mut work = Profile::new("work");
+    /// work.add_directory("/src/work");
+    /// let mut oss = Profile::new("oss");
+    /// oss.add_directory("/src/work/oss");
+    /// let profiles = ProfileCollection::from(vec![work, oss]);
+    /// let home = Path::new("/home/tester");
+    /// let (profile, dir) = profiles.find_for_directory(Path::new("/src/work/oss/tool"), home).unwrap();
+    /// assert_eq!("oss", profile.name());
+    /// assert_eq!(PathBuf::from("/src/work/oss"), dir);
+    /// assert!(profiles.find_for_directory(Path::new("/src"), home).is_none());
+    /// ```
+    pub fn find_for_directory(&self, dir: &Path, home: &Path) -> Option<(&Profile, PathBuf)> {
+        self.profiles
+            .iter()
+            .filter_map(|profile| Some((profile, profile.directory_for(dir, home)?)))
+            .max_by_key(|(_profile, directory)| directory.components().count())
+    }
+}
+
+impl From<Vec<Profile>> for ProfileCollection {
+    fn from(profiles: Vec<Profile>) -> Self {
+        ProfileCollection { profiles }
+    }
+}
+
+impl AsRef<ProfileCollection> for ProfileCollection {
+    fn as_ref(&self) -> &ProfileCollection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_home() {
+        let home = Path::new("/home/tester");
+        assert_eq!(PathBuf::from("/home/tester"), expand_home("~", home));
+        assert_eq!(PathBuf::from("/home/tester/work"), expand_home("~/work", home));
+        assert_eq!(PathBuf::from("/src/~work"), expand_home("/src/~work", home));
+    }
+
+    #[test]
+    fn test_add_and_remove_directory() {
+        let mut profiles = ProfileCollection::new();
+        assert!(profiles.find_or_add_profile("work").add_directory("~/work"));
+        assert!(!profiles.find_or_add_profile("work").add_directory("~/work"));
+        assert_eq!(1, profiles.profiles().len());
+
+        assert!(profiles.find_or_add_profile("work").remove_directory("~/work"));
+        profiles.remove_empty();
+        assert!(profiles.find_profile("work").is_none());
+    }
+}
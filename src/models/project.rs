@@ -0,0 +1,160 @@
+use std::fmt::{Display, Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::author::author_collection::AuthorCollection;
+use crate::models::author::{is_valid_alias, Author};
+
+/// Project specific configuration checked into a repository as `.pair-commit.yml`
+///
+/// Besides the authors listed here, the file can contain any setting from `config.yml`, such as
+/// `trailer` or `session`, which are layered over the global settings.
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::project::ProjectConfig;
+/// let yaml = "authors:\n  - name: Tester\n    email: tester@test.com\n    alias: tt";
+/// let project: ProjectConfig = serde_yaml::from_str(yaml).unwrap();
+/// assert_eq!(1, project.authors.len());
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    #[serde(default)]
+    pub authors: Vec<ProjectAuthor>,
+}
+
+/// An author shared by everybody working on a project
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ProjectAuthor {
+    pub name: String,
+    pub email: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+/// A project author that could not be taken as it is
+#[derive(Debug, Eq, PartialEq)]
+pub enum ImportIssue<'a> {
+    /// The author has an invalid name or email and is left out
+    InvalidAuthor(&'a ProjectAuthor),
+    /// The alias of the author is invalid or already in use and is left out
+    AliasLeftOut(&'a ProjectAuthor),
+}
+
+impl Display for ImportIssue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            ImportIssue::InvalidAuthor(author) => write!(
+                f,
+                "{} <{}> has an invalid name or email, so it is left out",
+                author.name, author.email
+            ),
+            ImportIssue::AliasLeftOut(author) => write!(
+                f,
+                "the alias '{}' of {} is invalid or already in use, so it is left out",
+                author.alias.as_deref().unwrap_or_default(),
+                author.email
+            ),
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// Adds the project authors that are not yet known by email, returning what could not be
+    /// taken as it is
+    ///
+    /// The added authors are marked as coming from the project file and get ids derived from
+    /// their email, so that they can be selected without being saved. Authors with an invalid
+    /// name or email are left out, as are aliases that are invalid or already in use.
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// # use pair_commit_tool::models::project::ProjectConfig;
+    /// let yaml = "authors:\n  - name: Tester\n    email: tester@test.com\n  \
+    ///             - name: Other\n    email: other@test.com";
+    /// let project: ProjectConfig = serde_yaml::from_str(yaml).unwrap();
+    /// let mut authors = AuthorCollection::from(vec![Author::new("Tester", "Tester@Test.com")]);
+    /// assert!(project.import_authors(&mut authors).is_empty());
+    /// assert_eq!(2, authors.authors().len());
+    /// assert!(authors.authors()[1].from_project());
+    /// ```
+    pub fn import_authors(&self, authors: &mut AuthorCollection) -> Vec<ImportIssue<'_>> {
+        let mut issues = Vec::new();
+        for project_author in &self.authors {
+            let mut author = match Author::try_new(&project_author.name, &project_author.email) {
+                Ok(author) => author,
+                Err(_) => {
+                    issues.push(ImportIssue::InvalidAuthor(project_author));
+                    continue;
+                }
+            };
+            if authors
+                .authors()
+                .iter()
+                .any(|known| known.has_email(author.email()))
+            {
+                continue;
+            }
+            if let Some(alias) = &project_author.alias {
+                if is_valid_alias(alias) && !authors.alias_in_use(alias, None) {
+                    author.set_alias(Some(alias));
+                } else {
+                    issues.push(ImportIssue::AliasLeftOut(project_author));
+                }
+            }
+            author.set_from_project(true);
+            authors.authors_mut().push(author);
+        }
+        authors.assign_missing_ids();
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_skips_alias_in_use() {
+        let yaml = "authors:\n  - name: Other\n    email: other@test.com\n    alias: tt";
+        let project: ProjectConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut tester = Author::new("Tester", "tester@test.com");
+        tester.set_alias(Some("tt"));
+        let mut authors = AuthorCollection::from(vec![tester]);
+        assert_eq!(1, project.import_authors(&mut authors).len());
+        assert_eq!(None, authors.authors()[1].alias());
+    }
+
+    #[test]
+    fn test_import_skips_invalid_email() {
+        let yaml = "authors:\n  - name: Other\n    email: other@test.comReviewed-by:x y";
+        let project: ProjectConfig = serde_yaml::from_str(yaml).unwrap();
+        let mut authors = AuthorCollection::new();
+        assert_eq!(
+            vec![ImportIssue::InvalidAuthor(&project.authors[0])],
+            project.import_authors(&mut authors)
+        );
+        assert!(authors.authors().is_empty());
+    }
+
+    #[test]
+    fn test_import_gives_the_same_ids_every_time() {
+        let yaml = "authors:\n  - name: Other\n    email: other@test.com";
+        let project: ProjectConfig = serde_yaml::from_str(yaml).unwrap();
+        let ids: Vec<String> = (0..2)
+            .map(|_| {
+                let mut authors = AuthorCollection::new();
+                project.import_authors(&mut authors);
+                authors.authors()[0].id().clone()
+            })
+            .collect();
+        assert!(!ids[0].is_empty());
+        assert_eq!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn test_deserialize_ignores_settings() {
+        let project: ProjectConfig = serde_yaml::from_str("trailer:\n  key: Paired-with").unwrap();
+        assert!(project.authors.is_empty());
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::author::role::Role;
+
+/// A pairing session, recording when the active co-authors were chosen and last committed with,
+/// and the role each of them has
+/// # Examples
+/// ```
+/// # use chrono::{Duration, TimeZone, Utc};
+/// # use pair_commit_tool::models::session::{Session, SessionExpiry};
+/// let started = Utc.with_ymd_and_hms(2020, 1, 1, 9, 0, 0).unwrap();
+/// let session = Session::start(started);
+/// let expiry = SessionExpiry::After(Duration::hours(8));
+/// assert!(!session.is_expired(expiry, started + Duration::hours(7)));
+/// assert!(session.is_expired(expiry, started + Duration::hours(8)));
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    started: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_commit: Option<DateTime<Utc>>,
+    /// Roles of co-authors by id, where co-authors without one are drivers
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    roles: BTreeMap<String, Role>,
+}
+
+impl Session {
+    pub fn start(now: DateTime<Utc>) -> Session {
+        Session {
+            started: Some(now),
+            ..Session::default()
+        }
+    }
+
+    /// Starts a new session, keeping the roles of the co-authors that are still active
+    /// # Examples
+    /// ```
+    /// # use chrono::Utc;
+    /// # use pair_commit_tool::models::author::role::Role;
+    /// # use pair_commit_tool::models::session::Session;
+    /// let mut session = Session::start(Utc::now());
+    /// session.set_role("0a1b2c3d", Role::Navigator);
+    /// session.set_role("4e5f6a7b", Role::Reviewer);
+    /// let restarted = session.restart(Utc::now(), &[String::from("0a1b2c3d")]);
+    /// assert_eq!(Role::Navigator, restarted.role("0a1b2c3d"));
+    /// assert_eq!(Role::Driver, restarted.role("4e5f6a7b"));
+    /// ```
+    pub fn restart(&self, now: DateTime<Utc>, active_ids: &[String]) -> Session {
+        let mut session = Session::start(now);
+        session.roles = self
+            .roles
+            .iter()
+            .filter(|(id, _role)| active_ids.contains(id))
+            .map(|(id, role)| (id.clone(), *role))
+            .collect();
+        session
+    }
+
+    pub fn roles(&self) -> &BTreeMap<String, Role> {
+        &self.roles
+    }
+
+    pub fn role(&self, id: &str) -> Role {
+        self.roles.get(id).copied().unwrap_or_default()
+    }
+
+    pub fn set_role(&mut self, id: &str, role: Role) {
+        if role == Role::default() {
+            self.roles.remove(id);
+        } else {
+            self.roles.insert(id.to_string(), role);
+        }
+    }
+
+    /// Moves the role of an author to the author they were merged into, unless that author has a
+    /// role of their own, returning true if the roles changed
+    /// # Examples
+    /// ```
+    /// # use chrono::Utc;
+    /// # use pair_commit_tool::models::author::role::Role;
+    /// # use pair_commit_tool::models::session::Session;
+    /// let mut session = Session::start(Utc::now());
+    /// session.set_role("4e5f6a7b", Role::Reviewer);
+    /// assert!(session.replace_reference("4e5f6a7b", "0a1b2c3d"));
+    /// assert_eq!(Role::Reviewer, session.role("0a1b2c3d"));
+    /// assert!(!session.replace_reference("4e5f6a7b", "0a1b2c3d"));
+    /// ```
+    pub fn replace_reference(&mut self, from: &str, to: &str) -> bool {
+        match self.roles.remove(from) {
+            Some(role) => {
+                self.roles.entry(to.to_string()).or_insert(role);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn started(&self) -> Option<DateTime<Utc>> {
+        self.started
+    }
+
+    pub fn last_commit(&self) -> Option<DateTime<Utc>> {
+        self.last_commit
+    }
+
+    pub fn record_commit(&mut self, now: DateTime<Utc>) {
+        self.last_commit = Some(now);
+    }
+
+    /// When the session expires, or `None` if it never does
+    ///
+    /// Sessions without a start time have not started yet, so they have no expiry until they are
+    /// started.
+    pub fn expires_at(&self, expiry: SessionExpiry) -> Option<DateTime<Utc>> {
+        self.expires_at_in(expiry, &Local)
+    }
+
+    /// When the session expires, with the end of the day taken in the given time zone
+    /// # Examples
+    /// ```
+    /// # use chrono::{Duration, FixedOffset, TimeZone, Utc};
+    /// # use pair_commit_tool::models::session::{Session, SessionExpiry};
+    /// let mut session = Session::start(Utc.with_ymd_and_hms(2020, 1, 1, 9, 0, 0).unwrap());
+    /// let end_of_day = session.expires_at_in(SessionExpiry::EndOfDay, &Utc);
+    /// assert_eq!(Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).single(), end_of_day);
+    ///
+    /// let offset = FixedOffset::east_opt(-10 * 3600).unwrap();
+    /// let end_of_day = session.expires_at_in(SessionExpiry::EndOfDay, &offset);
+    /// assert_eq!(Utc.with_ymd_and_hms(2020, 1, 1, 10, 0, 0).single(), end_of_day);
+    ///
+    /// session.record_commit(Utc.with_ymd_and_hms(2020, 1, 1, 11, 0, 0).unwrap());
+    /// let idle = session.expires_at_in(SessionExpiry::Idle(Duration::hours(1)), &Utc);
+    /// assert_eq!(Utc.with_ymd_and_hms(2020, 1, 1, 12, 0, 0).single(), idle);
+    /// ```
+    pub fn expires_at_in<Tz: TimeZone>(
+        &self,
+        expiry: SessionExpiry,
+        tz: &Tz,
+    ) -> Option<DateTime<Utc>> {
+        let started = self.started?;
+        match expiry {
+            SessionExpiry::Never => None,
+            SessionExpiry::After(duration) => Some(started + duration),
+            SessionExpiry::EndOfDay => {
+                let next_day = started.with_timezone(tz).date_naive().succ_opt()?;
+                tz.from_local_datetime(&next_day.and_time(NaiveTime::MIN))
+                    .earliest()
+                    .map(|midnight| midnight.with_timezone(&Utc))
+            }
+            SessionExpiry::Idle(duration) => {
+                let last_used = self.last_commit.map_or(started, |c| c.max(started));
+                Some(last_used + duration)
+            }
+        }
+    }
+
+    pub fn is_expired(&self, expiry: SessionExpiry, now: DateTime<Utc>) -> bool {
+        self.expires_at(expiry)
+            .is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// When a pairing session stops adding co-authors to commits
+/// # Examples
+/// ```
+/// # use chrono::Duration;
+/// # use pair_commit_tool::models::session::SessionExpiry;
+/// assert_eq!(Ok(SessionExpiry::EndOfDay), "end-of-day".parse());
+/// assert_eq!(Ok(SessionExpiry::After(Duration::minutes(90))), "1h30m".parse());
+/// assert_eq!(Ok(SessionExpiry::Idle(Duration::hours(2))), "idle 2h".parse());
+/// assert_eq!("after 1h30m", SessionExpiry::After(Duration::minutes(90)).to_string());
+/// ```
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SessionExpiry {
+    /// The session lasts until the co-authors are changed
+    #[default]
+    Never,
+    /// The session expires a fixed time after it started
+    After(Duration),
+    /// The session expires at the end of the local day it started on
+    EndOfDay,
+    /// The session expires when there has not been a commit for some time
+    Idle(Duration),
+}
+
+impl FromStr for SessionExpiry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("never") {
+            Ok(SessionExpiry::Never)
+        } else if s.eq_ignore_ascii_case("end-of-day") {
+            Ok(SessionExpiry::EndOfDay)
+        } else if let Some(duration) = s.strip_prefix("idle ") {
+            Ok(SessionExpiry::Idle(parse_duration(duration)?))
+        } else {
+            let duration = s.strip_prefix("after ").unwrap_or(s);
+            Ok(SessionExpiry::After(parse_duration(duration)?))
+        }
+    }
+}
+
+impl TryFrom<String> for SessionExpiry {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<SessionExpiry> for String {
+    fn from(expiry: SessionExpiry) -> Self {
+        expiry.to_string()
+    }
+}
+
+impl Display for SessionExpiry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            SessionExpiry::Never => write!(f, "never"),
+            SessionExpiry::After(duration) => write!(f, "after {}", format_duration(*duration)),
+            SessionExpiry::EndOfDay => write!(f, "end-of-day"),
+            SessionExpiry::Idle(duration) => write!(f, "idle {}", format_duration(*duration)),
+        }
+    }
+}
+
+const DURATION_UNITS: [(char, i64); 4] = [('d', 86400), ('h', 3600), ('m', 60), ('s', 1)];
+
+/// Parses durations such as `8h` or `1h30m`
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "'{}' is not a valid session expiry, use never, end-of-day, a duration such as 8h \
+             or 1h30m, or idle followed by a duration",
+            s
+        )
+    };
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    for c in s.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let (_, unit_seconds) = DURATION_UNITS
+            .iter()
+            .find(|(unit, _)| *unit == c.to_ascii_lowercase())
+            .ok_or_else(invalid)?;
+        let value: i64 = number.parse().map_err(|_| invalid())?;
+        seconds = value
+            .checked_mul(*unit_seconds)
+            .and_then(|unit_total| seconds.checked_add(unit_total))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() || seconds <= 0 {
+        return Err(invalid());
+    }
+    Duration::try_seconds(seconds).ok_or_else(invalid)
+}
+
+fn format_duration(duration: Duration) -> String {
+    let mut seconds = duration.num_seconds();
+    let mut formatted = String::new();
+    for (unit, unit_seconds) in DURATION_UNITS.iter() {
+        if seconds >= *unit_seconds {
+            formatted.push_str(&format!("{}{}", seconds / unit_seconds, unit));
+            seconds %= unit_seconds;
+        }
+    }
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2020, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_session_without_start_never_expires() {
+        let session = Session::default();
+        assert_eq!(None, session.expires_at(SessionExpiry::EndOfDay));
+        assert!(!session.is_expired(SessionExpiry::After(Duration::seconds(1)), time(23)));
+    }
+
+    #[test]
+    fn test_idle_without_commit_counts_from_start() {
+        let session = Session::start(time(9));
+        let expiry = SessionExpiry::Idle(Duration::hours(2));
+        assert_eq!(Some(time(11)), session.expires_at_in(expiry, &Utc));
+    }
+
+    #[test]
+    fn test_parse_invalid_expiry() {
+        assert!("soon".parse::<SessionExpiry>().is_err());
+        assert!("8".parse::<SessionExpiry>().is_err());
+        assert!("idle".parse::<SessionExpiry>().is_err());
+        assert!("0h".parse::<SessionExpiry>().is_err());
+        assert!("9999999999999999d".parse::<SessionExpiry>().is_err());
+    }
+
+    #[test]
+    fn test_expiry_round_trips_through_yaml() {
+        let expiry = SessionExpiry::Idle(Duration::minutes(45));
+        let yaml = serde_yaml::to_string(&expiry).unwrap();
+        assert_eq!(expiry, serde_yaml::from_str(&yaml).unwrap());
+    }
+
+    #[test]
+    fn test_serialize_session() {
+        let yaml = serde_yaml::to_string(&Session::start(time(9))).unwrap();
+        assert_eq!("---\nstarted: \"2020-01-01T09:00:00Z\"", yaml.trim_end());
+    }
+}
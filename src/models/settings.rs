@@ -0,0 +1,731 @@
+use std::fmt::{Display, Error, Formatter};
+use std::path::PathBuf;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_yaml::{Mapping, Value};
+
+use crate::models::author::author_collection::AuthorCollection;
+use crate::models::author::role::Role;
+use crate::models::author::trailer::{
+    is_valid_trailer_format, TrailerTemplate, DEFAULT_TRAILER_FORMAT,
+};
+use crate::models::author::{Author, COAUTHOR_TRAILER_KEY};
+
+use crate::models::commit_message::{is_valid_trailer_key, CommitSource};
+use crate::models::session::SessionExpiry;
+
+/// User settings stored alongside the co-author data
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::settings::{HookPolicy, Settings};
+/// # use pair_commit_tool::models::commit_message::CommitSource;
+/// let settings: Settings = serde_yaml::from_str("hook:\n  merge: append").unwrap();
+/// assert_eq!(HookPolicy::Append, settings.hook.policy_for(CommitSource::Merge));
+/// assert_eq!(HookPolicy::Skip, settings.hook.policy_for(CommitSource::Squash));
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub hook: HookSettings,
+    pub session: SessionSettings,
+    pub trailer: TrailerSettings,
+    pub state: StateSettings,
+    pub output: OutputSettings,
+}
+
+/// Merges settings from `overlay` into `base`, with values in `overlay` taking precedence
+///
+/// Mappings are merged key by key so that a layer only needs to contain the settings it changes.
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::settings::{merge_settings, HookPolicy, Settings};
+/// let mut base = serde_yaml::from_str("hook:\n  merge: append\n  squash: append").unwrap();
+/// merge_settings(&mut base, serde_yaml::from_str("hook:\n  squash: replace").unwrap());
+/// let settings: Settings = serde_yaml::from_value(base).unwrap();
+/// assert_eq!(HookPolicy::Append, settings.hook.merge);
+/// assert_eq!(HookPolicy::Replace, settings.hook.squash);
+/// ```
+pub fn merge_settings(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_settings(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Policies applied by the `prepare-commit-msg` hook for each commit message source
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookSettings {
+    pub message: HookPolicy,
+    pub template: HookPolicy,
+    pub merge: HookPolicy,
+    pub squash: HookPolicy,
+    pub commit: HookPolicy,
+    pub amend: HookPolicy,
+}
+
+impl Default for HookSettings {
+    fn default() -> Self {
+        HookSettings {
+            message: HookPolicy::Append,
+            template: HookPolicy::Append,
+            merge: HookPolicy::Skip,
+            squash: HookPolicy::Skip,
+            commit: HookPolicy::Skip,
+            amend: HookPolicy::Skip,
+        }
+    }
+}
+
+impl HookSettings {
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::settings::{HookPolicy, HookSettings};
+    /// # use pair_commit_tool::models::commit_message::CommitSource;
+    /// let settings = HookSettings::default();
+    /// assert_eq!(HookPolicy::Append, settings.policy_for(CommitSource::Editor));
+    /// assert_eq!(HookPolicy::Skip, settings.policy_for(CommitSource::Commit));
+    /// assert_eq!(HookPolicy::Skip, settings.policy_for(CommitSource::Amend));
+    /// ```
+    pub fn policy_for(&self, source: CommitSource) -> HookPolicy {
+        match source {
+            CommitSource::Editor => HookPolicy::Append,
+            CommitSource::Message => self.message,
+            CommitSource::Template => self.template,
+            CommitSource::Merge => self.merge,
+            CommitSource::Squash => self.squash,
+            CommitSource::Commit => self.commit,
+            CommitSource::Amend => self.amend,
+        }
+    }
+}
+
+/// Format of the trailers crediting co-authors
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrailerSettings {
+    #[serde(deserialize_with = "deserialize_trailer_key")]
+    pub key: String,
+    #[serde(deserialize_with = "deserialize_trailer_format")]
+    pub format: String,
+    pub order: TrailerOrder,
+    pub roles: RoleTrailerKeys,
+}
+
+impl Default for TrailerSettings {
+    fn default() -> Self {
+        TrailerSettings {
+            key: COAUTHOR_TRAILER_KEY.to_string(),
+            format: DEFAULT_TRAILER_FORMAT.to_string(),
+            order: TrailerOrder::default(),
+            roles: RoleTrailerKeys::default(),
+        }
+    }
+}
+
+/// Trailer keys crediting co-authors that do not drive, who are credited with the key of
+/// [`TrailerSettings`]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoleTrailerKeys {
+    #[serde(deserialize_with = "deserialize_trailer_key")]
+    pub navigator: String,
+    #[serde(deserialize_with = "deserialize_trailer_key")]
+    pub reviewer: String,
+}
+
+impl Default for RoleTrailerKeys {
+    fn default() -> Self {
+        RoleTrailerKeys {
+            navigator: String::from("Helped-by"),
+            reviewer: String::from("Reviewed-by"),
+        }
+    }
+}
+
+fn deserialize_trailer_key<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let key = String::deserialize(deserializer)?;
+    if is_valid_trailer_key(&key) {
+        Ok(key)
+    } else {
+        Err(de::Error::custom(format!(
+            "'{}' is not a valid trailer key, use letters, digits and '-' only",
+            key
+        )))
+    }
+}
+
+fn deserialize_trailer_format<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    let format = String::deserialize(deserializer)?;
+    if is_valid_trailer_format(&format) {
+        Ok(format)
+    } else {
+        Err(de::Error::custom(format!(
+            "'{}' is not a valid trailer format, use text on a single line with at least one of \
+             {{name}}, {{email}} and {{alias}}",
+            format
+        )))
+    }
+}
+
+impl TrailerSettings {
+    /// The configured trailer key and format for co-authors with the given role
+    pub fn template(&self, role: Role) -> TrailerTemplate {
+        TrailerTemplate::new(self.key_for(role), &self.format)
+    }
+
+    /// The trailer key crediting co-authors with the given role
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::role::Role;
+    /// # use pair_commit_tool::models::settings::TrailerSettings;
+    /// let settings = TrailerSettings::default();
+    /// assert_eq!("Co-authored-by", settings.key_for(Role::Driver));
+    /// assert_eq!("Reviewed-by", settings.key_for(Role::Reviewer));
+    /// ```
+    pub fn key_for(&self, role: Role) -> &str {
+        match role {
+            Role::Driver => &self.key,
+            Role::Navigator => &self.roles.navigator,
+            Role::Reviewer => &self.roles.reviewer,
+        }
+    }
+
+    /// The trailer keys crediting the co-authors, which always include the key of drivers and
+    /// otherwise only the keys of roles some co-author has
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::role::Role;
+    /// # use pair_commit_tool::models::author::ActiveState::ACTIVE;
+    /// # use pair_commit_tool::models::author::Author;
+    /// # use pair_commit_tool::models::settings::TrailerSettings;
+    /// let settings = TrailerSettings::default();
+    /// let mut authors =
+    ///     AuthorCollection::from(vec![Author::with_active_state("Tester", "tester@test.com", ACTIVE)]);
+    /// assert_eq!(vec!["Co-authored-by"], settings.keys_for(&authors));
+    /// authors.authors_mut()[0].set_role(Role::Reviewer);
+    /// assert_eq!(vec!["Co-authored-by", "Reviewed-by"], settings.keys_for(&authors));
+    /// ```
+    pub fn keys_for(&self, authors: &AuthorCollection) -> Vec<&str> {
+        let mut keys: Vec<&str> = vec![&self.key];
+        for author in authors.coauthors() {
+            let key = self.key_for(author.role());
+            if !keys.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+
+    /// Trailers crediting every co-author with the key of their role, in the configured order
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::ActiveState::ACTIVE;
+    /// # use pair_commit_tool::models::author::Author;
+    /// # use pair_commit_tool::models::settings::{TrailerOrder, TrailerSettings};
+    /// let authors = AuthorCollection::from(vec![
+    ///     Author::with_active_state("Tester", "tester@test.com", ACTIVE),
+    ///     Author::with_active_state("Other", "other@test.com", ACTIVE),
+    /// ]);
+    /// let settings = TrailerSettings {
+    ///     order: TrailerOrder::Name,
+    ///     ..TrailerSettings::default()
+    /// };
+    /// assert_eq!(
+    ///     vec!["Co-authored-by: Other <other@test.com>", "Co-authored-by: Tester <tester@test.com>"],
+    ///     settings.trailers(&authors)
+    /// );
+    /// ```
+    pub fn trailers(&self, authors: &AuthorCollection) -> Vec<String> {
+        let mut active: Vec<&Author> = authors.coauthors();
+        match self.order {
+            TrailerOrder::Added => {}
+            TrailerOrder::Name => active.sort_by_key(|author| author.name().to_lowercase()),
+            TrailerOrder::Email => active.sort_by_key(|author| author.email().to_lowercase()),
+        }
+        active
+            .iter()
+            .map(|author| self.template(author.role()).render(author))
+            .collect()
+    }
+}
+
+/// Order of the trailers crediting co-authors
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailerOrder {
+    /// The order the co-authors were added in
+    #[default]
+    Added,
+    /// Alphabetically by name
+    Name,
+    /// Alphabetically by email
+    Email,
+}
+
+/// Defaults for output meant to be read by people or other tools
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputSettings {
+    pub format: OutputFormat,
+}
+
+/// Format used to list co-authors
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Descriptions meant to be read, including indexes
+    #[default]
+    Text,
+    /// The stored co-author data
+    Yaml,
+}
+
+/// Where the active co-authors and their session are kept
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateSettings {
+    pub scope: StateScope,
+}
+
+/// Which repositories share the same active co-authors
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateScope {
+    /// Every repository shares the active co-authors
+    #[default]
+    Global,
+    /// Each repository has its own active co-authors, shared by its worktrees
+    Repository,
+    /// Each worktree of a repository has its own active co-authors
+    Worktree,
+}
+
+/// How long pairing sessions last
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionSettings {
+    pub expiry: SessionExpiry,
+}
+
+/// What the hook does with the co-author trailers of a commit message
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookPolicy {
+    /// Add active co-authors that are missing from the message
+    Append,
+    /// Replace the co-authors in the message with the active co-authors
+    Replace,
+    /// Leave the message alone
+    Skip,
+}
+
+/// Where the value of a setting comes from
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SettingOrigin {
+    Default,
+    File(PathBuf),
+    Environment(String),
+    CommandLine,
+}
+
+impl Display for SettingOrigin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            SettingOrigin::Default => write!(f, "default"),
+            SettingOrigin::File(path) => write!(f, "file:{}", path.display()),
+            SettingOrigin::Environment(variable) => write!(f, "env:{}", variable),
+            SettingOrigin::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+/// Settings layered from several sources, remembering where each value comes from
+///
+/// Settings are identified by keys such as `trailer.key`, and later layers take precedence over
+/// earlier ones, starting with the defaults.
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::settings::{setting_layer, SettingOrigin, SettingsLayers};
+/// let mut layers = SettingsLayers::new();
+/// let layer = setting_layer("trailer.key", "Paired-with").unwrap();
+/// layers.push(SettingOrigin::CommandLine, layer).unwrap();
+/// assert_eq!("Paired-with", layers.settings().unwrap().trailer.key);
+/// assert_eq!(
+///     Some((String::from("never"), &SettingOrigin::Default)),
+///     layers.get("session.expiry")
+/// );
+/// ```
+#[derive(Debug)]
+pub struct SettingsLayers {
+    layers: Vec<(SettingOrigin, Value)>,
+}
+
+impl Default for SettingsLayers {
+    fn default() -> Self {
+        SettingsLayers::new()
+    }
+}
+
+impl SettingsLayers {
+    pub fn new() -> SettingsLayers {
+        SettingsLayers {
+            layers: vec![(SettingOrigin::Default, default_settings_value())],
+        }
+    }
+
+    /// Adds a layer over the others, failing if any of its settings is unknown or invalid
+    pub fn push(&mut self, origin: SettingOrigin, layer: Value) -> Result<(), String> {
+        self.push_allowing(origin, layer, &[])
+    }
+
+    /// Adds the layer of a project file over the others, which may also contain the authors of the
+    /// project
+    pub fn push_project(&mut self, origin: SettingOrigin, layer: Value) -> Result<(), String> {
+        self.push_allowing(origin, layer, PROJECT_KEYS)
+    }
+
+    fn push_allowing(
+        &mut self,
+        origin: SettingOrigin,
+        layer: Value,
+        allowed: &[&str],
+    ) -> Result<(), String> {
+        if layer.is_null() {
+            return Ok(());
+        }
+        if let Some(key) = unknown_key(&layer, allowed) {
+            return Err(format!(
+                "Invalid settings in {}: unknown setting '{}'",
+                origin, key
+            ));
+        }
+        validate_layer(&layer).map_err(|e| format!("Invalid settings in {}: {}", origin, e))?;
+        self.layers.push((origin, layer));
+        Ok(())
+    }
+
+    pub fn settings(&self) -> Result<Settings, serde_yaml::Error> {
+        let mut merged = Value::Mapping(Mapping::new());
+        for (_origin, layer) in &self.layers {
+            merge_settings(&mut merged, layer.clone());
+        }
+        serde_yaml::from_value(merged)
+    }
+
+    /// The value of a setting with the layer it comes from, or `None` if there is no such setting
+    pub fn get(&self, key: &str) -> Option<(String, &SettingOrigin)> {
+        self.layers.iter().rev().find_map(|(origin, layer)| {
+            lookup_setting(layer, key)
+                .filter(|value| is_setting_value(value))
+                .map(|value| (format_setting_value(value), origin))
+        })
+    }
+
+    /// Every setting with its value and the layer it comes from
+    pub fn list(&self) -> Vec<(String, String, &SettingOrigin)> {
+        setting_keys()
+            .into_iter()
+            .filter_map(|key| {
+                let (value, origin) = self.get(&key)?;
+                Some((key, value, origin))
+            })
+            .collect()
+    }
+}
+
+/// Keys of every setting, such as `trailer.key`
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::settings::setting_keys;
+/// assert!(setting_keys().contains(&String::from("hook.merge")));
+/// assert!(!setting_keys().contains(&String::from("hook")));
+/// ```
+pub fn setting_keys() -> Vec<String> {
+    let mut keys = Vec::new();
+    collect_keys(&default_settings_value(), "", &mut keys);
+    keys
+}
+
+/// Name of the environment variable overriding a setting
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::settings::environment_variable;
+/// assert_eq!("PAIR_COMMIT_TRAILER_KEY", environment_variable("trailer.key"));
+/// ```
+pub fn environment_variable(key: &str) -> String {
+    format!("PAIR_COMMIT_{}", key.replace('.', "_").to_uppercase())
+}
+
+/// A layer containing only the given setting, failing if there is no such setting or the value is
+/// invalid for it
+pub fn setting_layer(key: &str, value: &str) -> Result<Value, String> {
+    let mut layer = Value::Mapping(Mapping::new());
+    set_setting(&mut layer, key, value)?;
+    Ok(layer)
+}
+
+/// Sets a setting in a layer, such as the contents of a settings file, leaving everything else as
+/// it is
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::settings::set_setting;
+/// let mut layer = serde_yaml::from_str("hook:\n  merge: append").unwrap();
+/// set_setting(&mut layer, "hook.squash", "replace").unwrap();
+/// assert_eq!(
+///     serde_yaml::from_str::<serde_yaml::Value>("hook:\n  merge: append\n  squash: replace").unwrap(),
+///     layer
+/// );
+/// assert!(set_setting(&mut layer, "hook.squash", "sometimes").is_err());
+/// assert!(set_setting(&mut layer, "hook.sometimes", "append").is_err());
+/// ```
+pub fn set_setting(layer: &mut Value, key: &str, value: &str) -> Result<(), String> {
+    if !setting_keys().iter().any(|k| k == key) {
+        return Err(format!("Unknown setting '{}'", key));
+    }
+    let mut single = Value::String(value.to_string());
+    for part in key.rsplit('.') {
+        let mut mapping = Mapping::new();
+        mapping.insert(Value::String(part.to_string()), single);
+        single = Value::Mapping(mapping);
+    }
+    validate_layer(&single).map_err(|e| format!("Invalid value for {}: {}", key, e))?;
+    if !layer.is_mapping() {
+        *layer = Value::Mapping(Mapping::new());
+    }
+    merge_settings(layer, single);
+    Ok(())
+}
+
+fn default_settings_value() -> Value {
+    serde_yaml::to_value(Settings::default()).expect("Default settings can be serialized")
+}
+
+fn validate_layer(layer: &Value) -> Result<(), serde_yaml::Error> {
+    let mut merged = default_settings_value();
+    merge_settings(&mut merged, layer.clone());
+    serde_yaml::from_value::<Settings>(merged).map(|_| ())
+}
+
+/// Top level keys of a project file that are not settings
+const PROJECT_KEYS: &[&str] = &["authors"];
+
+/// The first key of the layer that is neither a setting, a group of settings such as `trailer`,
+/// nor one of the allowed top level keys
+fn unknown_key(layer: &Value, allowed: &[&str]) -> Option<String> {
+    let known = setting_keys();
+    let mut keys = Vec::new();
+    collect_keys(layer, "", &mut keys);
+    keys.into_iter().find(|key| {
+        let top = key.split('.').next().unwrap_or_default();
+        !allowed.contains(&top)
+            && !known
+                .iter()
+                .any(|k| k == key || k.starts_with(&format!("{}.", key)))
+    })
+}
+
+fn collect_keys(value: &Value, prefix: &str, keys: &mut Vec<String>) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                if let Some(key) = key.as_str() {
+                    let key = if prefix.is_empty() {
+                        key.to_string()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    collect_keys(value, &key, keys);
+                }
+            }
+        }
+        _ => keys.push(prefix.to_string()),
+    }
+}
+
+fn lookup_setting<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.')
+        .try_fold(value, |value, part| value.as_mapping()?.get(&Value::from(part)))
+}
+
+fn is_setting_value(value: &Value) -> bool {
+    !value.is_mapping() && !value.is_sequence()
+}
+
+fn format_setting_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::author::ActiveState;
+
+    #[test]
+    fn test_deserialize_empty_settings() {
+        let settings: Settings = serde_yaml::from_str("{}").unwrap();
+        assert_eq!(Settings::default(), settings);
+    }
+
+    #[test]
+    fn test_deserialize_hook_policies() {
+        let settings: Settings =
+            serde_yaml::from_str("hook:\n  commit: replace\n  message: skip").unwrap();
+        assert_eq!(HookPolicy::Replace, settings.hook.commit);
+        assert_eq!(HookPolicy::Skip, settings.hook.message);
+        assert_eq!(HookPolicy::Append, settings.hook.template);
+    }
+
+    #[test]
+    fn test_deserialize_session_expiry() {
+        let settings: Settings = serde_yaml::from_str("session:\n  expiry: end-of-day").unwrap();
+        assert_eq!(SessionExpiry::EndOfDay, settings.session.expiry);
+        assert!(serde_yaml::from_str::<Settings>("session:\n  expiry: soon").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_state_scope() {
+        let settings: Settings = serde_yaml::from_str("state:\n  scope: worktree").unwrap();
+        assert_eq!(StateScope::Worktree, settings.state.scope);
+        assert_eq!(StateScope::Global, Settings::default().state.scope);
+    }
+
+    #[test]
+    fn test_layers_report_origin() {
+        let mut layers = SettingsLayers::new();
+        let file = SettingOrigin::File(PathBuf::from("config.yml"));
+        let layer = serde_yaml::from_str("trailer:\n  order: name\nauthors: []").unwrap();
+        layers.push_project(file.clone(), layer).unwrap();
+        let variable = environment_variable("trailer.order");
+        let layer = setting_layer("trailer.order", "email").unwrap();
+        layers
+            .push(SettingOrigin::Environment(variable.clone()), layer)
+            .unwrap();
+
+        let (value, origin) = layers.get("trailer.order").unwrap();
+        assert_eq!("email", value);
+        assert_eq!("env:PAIR_COMMIT_TRAILER_ORDER", origin.to_string());
+        assert_eq!(TrailerOrder::Email, layers.settings().unwrap().trailer.order);
+        assert_eq!(None, layers.get("authors"));
+        assert_eq!(setting_keys().len(), layers.list().len());
+    }
+
+    #[test]
+    fn test_push_invalid_layer() {
+        let mut layers = SettingsLayers::new();
+        let layer = serde_yaml::from_str("output:\n  format: xml").unwrap();
+        let error = layers
+            .push(SettingOrigin::File(PathBuf::from("config.yml")), layer)
+            .unwrap_err();
+        assert!(error.starts_with("Invalid settings in file:config.yml: "));
+    }
+
+    #[test]
+    fn test_push_unknown_keys() {
+        let mut layers = SettingsLayers::new();
+        let file = SettingOrigin::File(PathBuf::from("config.yml"));
+        for yaml in &[
+            "trailer:\n  kye: Paired-with",
+            "sesion:\n  expiry: never",
+            "authors: []",
+        ] {
+            let layer = serde_yaml::from_str(yaml).unwrap();
+            assert!(layers.push(file.clone(), layer).is_err(), "{}", yaml);
+        }
+        let layer = serde_yaml::from_str("trailer:\n  kye: Paired-with\nauthors: []").unwrap();
+        let error = layers.push_project(file, layer).unwrap_err();
+        assert_eq!(
+            "Invalid settings in file:config.yml: unknown setting 'trailer.kye'",
+            error
+        );
+    }
+
+    #[test]
+    fn test_trailers_in_order_added() {
+        let authors = AuthorCollection::from(vec![
+            Author::with_active_state("Tester", "tester@test.com", ActiveState::ACTIVE),
+            Author::new("Inactive", "inactive@test.com"),
+            Author::with_active_state("Other", "a@test.com", ActiveState::ACTIVE),
+        ]);
+        let settings = TrailerSettings {
+            key: String::from("Paired-with"),
+            order: TrailerOrder::Added,
+            ..TrailerSettings::default()
+        };
+        assert_eq!(
+            vec![
+                "Paired-with: Tester <tester@test.com>",
+                "Paired-with: Other <a@test.com>"
+            ],
+            settings.trailers(&authors)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_invalid_policy() {
+        let settings = serde_yaml::from_str::<Settings>("hook:\n  merge: sometimes");
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn test_deserialize_invalid_trailer_key() {
+        let settings = serde_yaml::from_str::<Settings>("trailer:\n  key: Paired with");
+        assert!(settings.is_err());
+        let settings = serde_yaml::from_str::<Settings>("trailer:\n  key: Paired-with").unwrap();
+        assert_eq!("Paired-with", settings.trailer.key);
+    }
+
+    #[test]
+    fn test_trailers_by_role() {
+        let mut navigator =
+            Author::with_active_state("Other", "other@test.com", ActiveState::ACTIVE);
+        navigator.set_role(Role::Navigator);
+        let authors = AuthorCollection::from(vec![
+            Author::with_active_state("Tester", "tester@test.com", ActiveState::ACTIVE),
+            navigator,
+        ]);
+        let settings: Settings =
+            serde_yaml::from_str("trailer:\n  roles:\n    navigator: Assisted-by").unwrap();
+        assert_eq!(
+            vec![
+                "Co-authored-by: Tester <tester@test.com>",
+                "Assisted-by: Other <other@test.com>"
+            ],
+            settings.trailer.trailers(&authors)
+        );
+        let invalid = serde_yaml::from_str::<Settings>("trailer:\n  roles:\n    reviewer: a b");
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_trailer_format() {
+        let settings = serde_yaml::from_str::<Settings>("trailer:\n  format: '{nickname}'");
+        assert!(settings.is_err());
+        let settings: Settings =
+            serde_yaml::from_str("trailer:\n  key: Mob\n  format: '{alias}'").unwrap();
+        let authors = AuthorCollection::from(vec![Author::with_active_state(
+            "Tester",
+            "tester@test.com",
+            ActiveState::ACTIVE,
+        )]);
+        assert_eq!(vec!["Mob: Tester"], settings.trailer.trailers(&authors));
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+pub mod team_collection;
+
+/// A named group of authors that can be activated together
+///
+/// Members are stored by the id of the author, which survives edits to the author's name or email,
+/// rather than by their position in the author collection.
+/// # Examples
+/// ```
+/// # use pair_commit_tool::models::team::Team;
+/// let mut team = Team::new("core");
+/// team.add_member("3f9a1c2e");
+/// assert_eq!(&vec!["3f9a1c2e".to_string()], team.members());
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Team {
+    name: String,
+    #[serde(default)]
+    members: Vec<String>,
+}
+
+impl Team {
+    pub fn new<S: AsRef<str>>(name: S) -> Team {
+        Team {
+            name: name.as_ref().to_string(),
+            members: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn members(&self) -> &Vec<String> {
+        &self.members
+    }
+
+    /// Adds a member reference, returning false if it was already a member
+    pub fn add_member<S: AsRef<str>>(&mut self, member: S) -> bool {
+        let member = member.as_ref();
+        if self.has_member(member) {
+            false
+        } else {
+            self.members.push(member.to_string());
+            true
+        }
+    }
+
+    /// Removes a member reference, returning false if it was not a member
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::team::Team;
+    /// let mut team = Team::new("core");
+    /// team.add_member("tester@test.com");
+    /// assert!(team.remove_member("Tester@Test.com"));
+    /// assert!(!team.remove_member("tester@test.com"));
+    /// ```
+    pub fn remove_member(&mut self, member: &str) -> bool {
+        let count = self.members.len();
+        self.members.retain(|m| !m.eq_ignore_ascii_case(member));
+        count != self.members.len()
+    }
+
+    pub fn has_member(&self, member: &str) -> bool {
+        self.members.iter().any(|m| m.eq_ignore_ascii_case(member))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_member_twice() {
+        let mut team = Team::new("core");
+        assert!(team.add_member("3f9a1c2e"));
+        assert!(!team.add_member("3F9A1C2E"));
+        assert_eq!(1, team.members().len());
+    }
+
+    #[test]
+    fn test_serialize_team() {
+        let mut team = Team::new("core");
+        team.add_member("3f9a1c2e");
+        let expected = "---\n\
+                        name: core\n\
+                        members:\n  \
+                        - 3f9a1c2e";
+        assert_eq!(expected, serde_yaml::to_string(&team).unwrap());
+    }
+
+    #[test]
+    fn test_deserialize_team_without_members() {
+        let team: Team = serde_yaml::from_str("name: core").unwrap();
+        assert!(team.members().is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+use crate::models::author::author_collection::AuthorCollection;
+use crate::models::team::Team;
+
+#[derive(Default)]
+pub struct TeamCollection {
+    teams: Vec<Team>,
+}
+
+impl TeamCollection {
+    pub fn new() -> TeamCollection {
+        TeamCollection {
+            ..TeamCollection::default()
+        }
+    }
+
+    pub fn teams(&self) -> &Vec<Team> {
+        &self.teams
+    }
+
+    /// Adds a team, returning false if a team with the same name already exists
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::team::team_collection::TeamCollection;
+    /// # use pair_commit_tool::models::team::Team;
+    /// let mut teams = TeamCollection::new();
+    /// assert!(teams.add_team(Team::new("core")));
+    /// assert!(!teams.add_team(Team::new("Core")));
+    /// ```
+    pub fn add_team(&mut self, team: Team) -> bool {
+        if self.find_team(team.name()).is_some() {
+            false
+        } else {
+            self.teams.push(team);
+            true
+        }
+    }
+
+    /// Finds a team by name, ignoring case
+    pub fn find_team(&self, name: &str) -> Option<&Team> {
+        self.teams
+            .iter()
+            .find(|t| t.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn find_team_mut(&mut self, name: &str) -> Option<&mut Team> {
+        self.teams
+            .iter_mut()
+            .find(|t| t.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Replaces member references that are not current author references, such as the emails
+    /// used before authors had ids, returning true if any team changed
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::author::author_collection::AuthorCollection;
+    /// # use pair_commit_tool::models::author::Author;
+    /// # use pair_commit_tool::models::team::team_collection::TeamCollection;
+    /// # use pair_commit_tool::models::team::Team;
+    /// let mut authors = AuthorCollection::new();
+    /// authors.add_author(Author::new("Tester", "tester@test.com"));
+    /// let mut team = Team::new("core");
+    /// team.add_member("tester@test.com");
+    /// let mut teams = TeamCollection::from(vec![team]);
+    /// assert!(teams.update_references(&authors));
+    /// assert!(teams.teams()[0].has_member(authors.authors()[0].id()));
+    /// ```
+    pub fn update_references(&mut self, authors: &AuthorCollection) -> bool {
+        let mut changed = false;
+        for member in self
+            .teams
+            .iter_mut()
+            .flat_map(|team| team.members.iter_mut())
+        {
+            if let Some(index) = authors.find_author_index_by_reference(member) {
+                let reference = authors.authors()[index].reference();
+                if !reference.is_empty() && reference != member {
+                    *member = reference.clone();
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Makes members referring to one author refer to another, such as when the authors are
+    /// merged, returning true if any team changed
+    /// # Examples
+    /// ```
+    /// # use pair_commit_tool::models::team::team_collection::TeamCollection;
+    /// # use pair_commit_tool::models::team::Team;
+    /// let mut team = Team::new("core");
+    /// team.add_member("a");
+    /// team.add_member("b");
+    /// let mut teams = TeamCollection::from(vec![team]);
+    /// assert!(teams.replace_reference("b", "a"));
+    /// assert_eq!(&vec!["a".to_string()], teams.teams()[0].members());
+    /// ```
+    pub fn replace_reference(&mut self, from: &str, to: &str) -> bool {
+        let mut changed = false;
+        for team in self.teams.iter_mut() {
+            if !from.is_empty() && team.remove_member(from) {
+                team.add_member(to);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub fn remove_team(&mut self, name: &str) -> Option<Team> {
+        let index = self
+            .teams
+            .iter()
+            .position(|t| t.name().eq_ignore_ascii_case(name))?;
+        Some(self.teams.remove(index))
+    }
+}
+
+impl From<Vec<Team>> for TeamCollection {
+    fn from(teams: Vec<Team>) -> Self {
+        TeamCollection { teams }
+    }
+}
+
+impl AsRef<TeamCollection> for TeamCollection {
+    fn as_ref(&self) -> &TeamCollection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::team::team_collection::TeamCollection;
+    use crate::models::team::Team;
+
+    #[test]
+    fn test_find_team_ignores_case() {
+        let teams = TeamCollection::from(vec![Team::new("Core")]);
+        assert!(teams.find_team("core").is_some());
+        assert!(teams.find_team("other").is_none());
+    }
+
+    #[test]
+    fn test_find_team_mut() {
+        let mut teams = TeamCollection::from(vec![Team::new("core")]);
+        teams
+            .find_team_mut("core")
+            .unwrap()
+            .add_member("tester@test.com");
+        assert_eq!(1, teams.find_team("core").unwrap().members().len());
+    }
+
+    #[test]
+    fn test_remove_team() {
+        let mut teams = TeamCollection::from(vec![Team::new("core"), Team::new("other")]);
+        assert_eq!("core", teams.remove_team("CORE").unwrap().name());
+        assert!(teams.remove_team("core").is_none());
+        assert_eq!(1, teams.teams().len());
+    }
+}
@@ -1,66 +1,373 @@
-use std::fs::{create_dir, File};
-use std::io::{ErrorKind, Write};
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::fs::{self, create_dir_all, File, OpenOptions, TryLockError};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use pair_commit_tool::models::author::author_collection::AuthorCollection;
 use pair_commit_tool::models::author::Author;
+use pair_commit_tool::models::branch_pair::{BranchPair, BranchPairCollection};
+use pair_commit_tool::models::data_file::{
+    authors_value, empty_document, set_authors_value, upgrade, DataFileError,
+};
+use pair_commit_tool::models::profile::{Profile, ProfileCollection};
+use pair_commit_tool::models::project::ProjectConfig;
+use pair_commit_tool::models::session::Session;
+use pair_commit_tool::models::team::team_collection::TeamCollection;
+use pair_commit_tool::models::team::Team;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_yaml::Value;
 
-pub fn save<T: AsRef<AuthorCollection>>(file_path: PathBuf, authors: T) {
-    let parent: PathBuf = file_path.parent().unwrap().to_path_buf();
-    if !save_directory_exists(&parent) {
-        create_dir(parent).unwrap();
+use crate::error::Error;
+
+/// Number of earlier versions of the authors file that are kept, from `<file>.1` (the newest) to
+/// `<file>.5`
+pub const BACKUP_COUNT: usize = 5;
+/// How long to wait for another run of the tool to release a lock before giving up
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An advisory lock on a file, held until it is dropped
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
     }
+}
 
-    let mut file = match File::create(file_path) {
-        Ok(file) => file,
-        Err(error) => match error.kind() {
-            ErrorKind::NotFound => panic!("Problem creating the file: {:?}", error),
-            _ => panic!("Unexpected problem creating the file: {:?}", error),
-        },
-    };
+/// Locks a file against other runs of the tool, using `<file>.lock` so that the file itself can
+/// be replaced while the lock is held, and waits for a while if another run holds the lock
+pub fn lock(file_path: &Path) -> Result<FileLock, Error> {
+    let lock_path = sibling_path(file_path, ".lock");
+    create_parent_dir(&lock_path)?;
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| Error::write(&lock_path, e))?;
+    let start = Instant::now();
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(FileLock { file }),
+            Err(TryLockError::WouldBlock) if start.elapsed() < LOCK_TIMEOUT => {
+                thread::sleep(LOCK_RETRY_INTERVAL)
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(Error::io(
+                    format!(
+                        "Gave up waiting for another run of the tool to release {}",
+                        lock_path.display()
+                    ),
+                    io::Error::from(ErrorKind::WouldBlock),
+                ))
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(Error::io(
+                    format!("Failed to lock {}", lock_path.display()),
+                    e,
+                ))
+            }
+        }
+    }
+}
+
+/// Saves the authors, keeping the previous contents of the file as the newest backup along with
+/// any fields of the file this version of the tool does not know about
+pub fn save<T: AsRef<AuthorCollection>>(file_path: PathBuf, authors: T) -> Result<(), Error> {
+    write_authors(file_path, authors.as_ref(), true)
+}
+
+/// Saves the authors like [`save`], without keeping a backup, for files such as those given to a
+/// single run of the tool
+pub fn save_without_backup<T: AsRef<AuthorCollection>>(
+    file_path: PathBuf,
+    authors: T,
+) -> Result<(), Error> {
+    write_authors(file_path, authors.as_ref(), false)
+}
 
-    match serde_yaml::to_writer(&file, authors.as_ref().authors()) {
-        Ok(()) => match file.flush() {
-            Ok(s) => s,
-            Err(error) => panic!("Problem writing data to file: {:?}", error),
-        },
-        Err(e) => panic!("Problem serializing authors to writer: {:?}", e),
+fn write_authors(
+    file_path: PathBuf,
+    authors: &AuthorCollection,
+    keep_backup: bool,
+) -> Result<(), Error> {
+    let mut document = read_data_file(&file_path)?.unwrap_or_else(empty_document);
+    let authors = serde_yaml::to_value(authors.saved_authors())
+        .map_err(|e| Error::write(&file_path, io::Error::other(e)))?;
+    set_authors_value(&mut document, authors);
+    let contents = to_yaml(&file_path, &document)?;
+    if keep_backup {
+        back_up(&file_path, contents.as_bytes())?;
     }
+    write_atomically(&file_path, contents.as_bytes())
+}
+
+pub fn save_teams<T: AsRef<TeamCollection>>(file_path: PathBuf, teams: T) -> Result<(), Error> {
+    write_yaml(file_path, teams.as_ref().teams())
+}
+
+pub fn save_branch_pairs<T: AsRef<BranchPairCollection>>(
+    file_path: PathBuf,
+    pairs: T,
+) -> Result<(), Error> {
+    write_yaml(file_path, pairs.as_ref().pairs())
+}
+
+pub fn save_profiles<T: AsRef<ProfileCollection>>(
+    file_path: PathBuf,
+    profiles: T,
+) -> Result<(), Error> {
+    write_yaml(file_path, profiles.as_ref().profiles())
+}
+
+pub fn save_active(file_path: PathBuf, ids: &[String]) -> Result<(), Error> {
+    write_yaml(file_path, &ids)
+}
+
+pub fn save_settings_layer(file_path: PathBuf, layer: &Value) -> Result<(), Error> {
+    write_yaml(file_path, layer)
+}
+
+pub fn save_session(file_path: PathBuf, session: &Session) -> Result<(), Error> {
+    write_yaml(file_path, session)
+}
+
+fn write_yaml<T: Serialize>(file_path: PathBuf, value: &T) -> Result<(), Error> {
+    let contents = to_yaml(&file_path, value)?;
+    write_atomically(&file_path, contents.as_bytes())
+}
+
+fn to_yaml<T: Serialize>(file_path: &Path, value: &T) -> Result<String, Error> {
+    serde_yaml::to_string(value).map_err(|e| Error::write(file_path, io::Error::other(e)))
+}
+
+/// Writes to a temporary file next to the file and renames it over the file, so that the file
+/// is never left partly written
+fn write_atomically(file_path: &Path, contents: &[u8]) -> Result<(), Error> {
+    create_parent_dir(file_path)?;
+    let temp_path = sibling_path(file_path, &format!(".{}.tmp", process::id()));
+    let written = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, file_path));
+    written.map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        Error::write(file_path, e)
+    })
+}
+
+fn create_parent_dir(file_path: &Path) -> Result<(), Error> {
+    match file_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        Some(parent) if !save_directory_exists(parent) => create_dir_all(parent)
+            .map_err(|e| Error::io(format!("Failed to create {}", parent.display()), e)),
+        _ => Ok(()),
+    }
+}
+
+/// The path of the file with a suffix added to its name
+fn sibling_path(file_path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(file_path.as_os_str());
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// Path of a backup of the file, where 1 is the newest
+pub fn backup_path(file_path: &Path, number: usize) -> PathBuf {
+    sibling_path(file_path, &format!(".{}", number))
 }
 
-fn save_directory_exists(dir: &PathBuf) -> bool {
+/// Numbers and paths of the backups of the file that exist, newest first
+pub fn backups(file_path: &Path) -> Vec<(usize, PathBuf)> {
+    (1..=BACKUP_COUNT)
+        .map(|number| (number, backup_path(file_path, number)))
+        .filter(|(_number, path)| path.is_file())
+        .collect()
+}
+
+/// Keeps the current contents of the file as the newest backup, unless they are about to be
+/// saved again unchanged, and drops the oldest backup once there are too many
+fn back_up(file_path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let current = match fs::read(file_path) {
+        Ok(current) => current,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(Error::read(file_path, e)),
+    };
+    if current == contents {
+        return Ok(());
+    }
+    for number in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(file_path, number);
+        if from.is_file() {
+            let to = backup_path(file_path, number + 1);
+            fs::rename(&from, &to).map_err(|e| Error::write(&to, e))?;
+        }
+    }
+    write_atomically(&backup_path(file_path, 1), &current)
+}
+
+/// Replaces the authors with those of a backup, which makes the replaced authors the newest
+/// backup so that restoring can be undone
+pub fn restore(file_path: PathBuf, number: usize) -> Result<AuthorCollection, Error> {
+    let backup = backup_path(&file_path, number);
+    let authors = match read_data_file(&backup)? {
+        Some(document) => authors_from_data_file(&backup, &document)?,
+        None => {
+            return Err(Error::Validation(format!(
+                "There is no backup {} of {}",
+                number,
+                file_path.display()
+            )))
+        }
+    };
+    save(file_path, &authors)?;
+    Ok(authors)
+}
+
+fn save_directory_exists(dir: &Path) -> bool {
     dir.is_dir()
 }
 
-pub fn load(file_path: PathBuf) -> Result<AuthorCollection, serde_yaml::Error> {
-    let file = File::open(file_path);
+/// Reads a YAML file, or returns `None` if it does not exist
+fn read_yaml<T: DeserializeOwned>(file_path: &Path) -> Result<Option<T>, Error> {
+    match File::open(file_path) {
+        Ok(f) => serde_yaml::from_reader::<File, T>(f)
+            .map(Some)
+            .map_err(|e| Error::parse(file_path, e)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(Error::read(file_path, e)),
+    }
+}
 
-    match file {
-        Ok(f) => {
-            let vec = serde_yaml::from_reader::<File, Vec<Author>>(f);
-            match vec {
-                Ok(vec) => Ok(AuthorCollection::from(vec)),
-                Err(e) => Err(e),
+/// Reads the authors file, upgrading it from the format of earlier versions of the tool, or
+/// returns `None` if it does not exist
+///
+/// Files written by newer versions of the tool are refused, so that they are never overwritten.
+fn read_data_file(file_path: &Path) -> Result<Option<Value>, Error> {
+    match read_yaml::<Value>(file_path)? {
+        Some(document) => upgrade(document).map(Some).map_err(|e| match e {
+            DataFileError::Newer(_) => {
+                Error::Config(format!("Cannot use {}: {}", file_path.display(), e))
             }
-        }
-        Err(_) => Ok(AuthorCollection::new()),
+            DataFileError::Invalid(message) => {
+                Error::parse(file_path, serde::de::Error::custom(message))
+            }
+        }),
+        None => Ok(None),
+    }
+}
+
+fn authors_from_data_file(file_path: &Path, document: &Value) -> Result<AuthorCollection, Error> {
+    serde_yaml::from_value::<Vec<Author>>(authors_value(document))
+        .map(AuthorCollection::from)
+        .map_err(|e| Error::parse(file_path, e))
+}
+
+/// Loads the authors, deriving an id from the email for any author saved without one
+///
+/// Files in the format of earlier versions are upgraded when they are loaded, and saved in the
+/// current format the next time the authors change.
+pub fn load(file_path: PathBuf) -> Result<AuthorCollection, Error> {
+    let mut authors = match read_data_file(&file_path)? {
+        Some(document) => authors_from_data_file(&file_path, &document)?,
+        None => return Ok(AuthorCollection::new()),
+    };
+    authors.assign_missing_ids();
+    Ok(authors)
+}
+
+pub fn load_teams(file_path: PathBuf) -> Result<TeamCollection, Error> {
+    Ok(read_yaml::<Vec<Team>>(&file_path)?
+        .map(TeamCollection::from)
+        .unwrap_or_default())
+}
+
+pub fn load_branch_pairs(file_path: PathBuf) -> Result<BranchPairCollection, Error> {
+    Ok(read_yaml::<Vec<BranchPair>>(&file_path)?
+        .map(BranchPairCollection::from)
+        .unwrap_or_default())
+}
+
+pub fn load_profiles(file_path: PathBuf) -> Result<ProfileCollection, Error> {
+    Ok(read_yaml::<Vec<Profile>>(&file_path)?
+        .map(ProfileCollection::from)
+        .unwrap_or_default())
+}
+
+/// Loads the contents of a settings file, which are `Null` when the file is missing
+pub fn load_settings_layer(file_path: PathBuf) -> Result<Value, Error> {
+    Ok(read_yaml(&file_path)?.unwrap_or(Value::Null))
+}
+
+pub fn load_project(file_path: PathBuf) -> Result<ProjectConfig, Error> {
+    match read_yaml(&file_path)? {
+        None | Some(Value::Null) => Ok(ProjectConfig::default()),
+        Some(value) => serde_yaml::from_value(value).map_err(|e| Error::parse(&file_path, e)),
     }
 }
 
+/// Loads the ids of the active authors, or `None` if they were never saved
+pub fn load_active(file_path: PathBuf) -> Result<Option<Vec<String>>, Error> {
+    read_yaml(&file_path)
+}
+
+pub fn load_session(file_path: PathBuf) -> Result<Session, Error> {
+    Ok(read_yaml(&file_path)?.unwrap_or_default())
+}
+
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use std::path::PathBuf;
 
     use pair_commit_tool::models::author::author_collection::AuthorCollection;
     use pair_commit_tool::models::author::Author;
+    use pair_commit_tool::models::branch_pair::{BranchPair, BranchPairCollection};
+    use pair_commit_tool::models::profile::{Profile, ProfileCollection};
+
+    use chrono::Utc;
+    use pair_commit_tool::models::session::Session;
+    use pair_commit_tool::models::settings::{HookPolicy, SettingOrigin, Settings, SettingsLayers};
 
-    use crate::persistence::{load, save, save_directory_exists};
+    use pair_commit_tool::models::team::team_collection::TeamCollection;
+    use pair_commit_tool::models::team::Team;
+
+    use crate::persistence::{
+        backups, load, load_active, load_branch_pairs, load_profiles, load_project, load_session,
+        load_settings_layer, load_teams, lock, restore, save, save_active, save_branch_pairs,
+        save_directory_exists, save_profiles, save_session, save_settings_layer, save_teams,
+        BACKUP_COUNT,
+    };
 
     enum PersistenceFilePath {
         Basic,
         Missing,
         Writable,
+        WritableIds,
+        NumericId,
         MissingParent,
+        Settings,
+        Project,
+        Teams,
+        WritableTeams,
+        WritableSession,
+        WritableActive,
+        WritableBranchPairs,
+        WritableSettings,
+        WritableProfiles,
+        WritableBackups,
+        Versioned,
+        WritableVersioned,
+        Newer,
     }
 
     impl PersistenceFilePath {
@@ -69,7 +376,34 @@ mod tests {
                 PersistenceFilePath::Basic => "test_data/persistence/basic.yml",
                 PersistenceFilePath::Missing => "test_data/persistence/missing.yml",
                 PersistenceFilePath::Writable => "test_data/persistence/writable.yml",
+                PersistenceFilePath::WritableIds => "test_data/persistence/writable_ids.yml",
+                PersistenceFilePath::NumericId => "test_data/persistence/numeric_id.yml",
                 PersistenceFilePath::MissingParent => "test_data/missing/missing.yml",
+                PersistenceFilePath::Settings => "test_data/persistence/config.yml",
+                PersistenceFilePath::Project => "test_data/persistence/project.yml",
+                PersistenceFilePath::Teams => "test_data/persistence/teams.yml",
+                PersistenceFilePath::WritableTeams => "test_data/persistence/writable_teams.yml",
+                PersistenceFilePath::WritableActive => "test_data/persistence/writable_active.yml",
+                PersistenceFilePath::WritableBranchPairs => {
+                    "test_data/persistence/writable_branches.yml"
+                }
+                PersistenceFilePath::WritableProfiles => {
+                    "test_data/persistence/writable_profiles.yml"
+                }
+                PersistenceFilePath::WritableSettings => {
+                    "test_data/persistence/writable_config.yml"
+                }
+                PersistenceFilePath::Versioned => "test_data/persistence/versioned.yml",
+                PersistenceFilePath::WritableVersioned => {
+                    "test_data/persistence/writable_versioned.yml"
+                }
+                PersistenceFilePath::Newer => "test_data/persistence/newer.yml",
+                PersistenceFilePath::WritableBackups => {
+                    "test_data/persistence/writable_backups.yml"
+                }
+                PersistenceFilePath::WritableSession => {
+                    "test_data/persistence/writable_session.yml"
+                }
             };
             PathBuf::from(string)
         }
@@ -81,7 +415,42 @@ mod tests {
         let mut authors = AuthorCollection::new();
         let author = Author::default();
         authors.add_author(author);
-        save(path, &authors)
+        save(path, &authors).unwrap()
+    }
+
+    #[test]
+    fn test_backups_roll_over_and_restore() {
+        let path = PersistenceFilePath::WritableBackups.get_filepath();
+        let _ = std::fs::remove_file(&path);
+        for (_number, backup) in backups(&path) {
+            std::fs::remove_file(backup).unwrap();
+        }
+        let mut authors = AuthorCollection::new();
+        for count in 0..=BACKUP_COUNT + 1 {
+            authors.add_author(Author::new(format!("Tester {}", count), "tester@test.com"));
+            save(path.clone(), &authors).unwrap();
+        }
+        save(path.clone(), &authors).unwrap();
+        assert_eq!(BACKUP_COUNT, backups(&path).len());
+
+        let restored = restore(path.clone(), 1).unwrap();
+        assert_eq!(BACKUP_COUNT + 1, restored.authors().len());
+        assert_eq!(
+            BACKUP_COUNT + 1,
+            load(path.clone()).unwrap().authors().len()
+        );
+        restore(path.clone(), 1).unwrap();
+        assert_eq!(BACKUP_COUNT + 2, load(path).unwrap().authors().len());
+    }
+
+    #[test]
+    fn test_lock_is_exclusive() {
+        let path = PersistenceFilePath::Writable.get_filepath();
+        let held = lock(&path).unwrap();
+        let file = std::fs::File::open(path.with_extension("yml.lock")).unwrap();
+        assert!(file.try_lock().is_err());
+        drop(held);
+        assert!(file.try_lock().is_ok());
     }
 
     #[test]
@@ -91,6 +460,42 @@ mod tests {
         assert_eq!(true, data.is_ok());
     }
 
+    #[test]
+    fn test_save_keeps_unknown_fields() {
+        let path = PersistenceFilePath::WritableVersioned.get_filepath();
+        let _ = std::fs::remove_file(&path);
+        std::fs::copy(PersistenceFilePath::Versioned.get_filepath(), &path).unwrap();
+        let mut authors = load(path.clone()).unwrap();
+        assert_eq!("Tester", authors.authors()[0].name());
+        authors.add_author(Author::new("Other", "other@test.com"));
+        save(path.clone(), &authors).unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with("---\nversion: 1\nauthors:"));
+        assert!(saved.contains("pronouns: they/them"));
+        assert!(saved.contains("sessions:"));
+        assert_eq!(2, load(path).unwrap().authors().len());
+    }
+
+    #[test]
+    fn test_newer_version_is_refused() {
+        let path = PersistenceFilePath::Newer.get_filepath();
+        match load(path.clone()) {
+            Err(error) => assert_eq!(crate::error::EXIT_CONFIG, error.exit_code()),
+            Ok(_authors) => panic!("A newer version was loaded"),
+        }
+        assert!(save(path, AuthorCollection::new()).is_err());
+    }
+
+    #[test]
+    fn test_load_invalid() {
+        let path = PersistenceFilePath::Settings.get_filepath();
+        match load(path) {
+            Err(error) => assert_eq!(crate::error::EXIT_PARSE, error.exit_code()),
+            Ok(_authors) => panic!("Settings were loaded as authors"),
+        }
+    }
+
     #[test]
     fn test_load_existing() {
         let path = PersistenceFilePath::Basic.get_filepath();
@@ -100,6 +505,156 @@ mod tests {
         assert_eq!(false, authors.authors().is_empty())
     }
 
+    #[test]
+    fn test_load_derives_the_same_ids() {
+        let path = PersistenceFilePath::WritableIds.get_filepath();
+        save(
+            path.clone(),
+            AuthorCollection::from(vec![Author::new("Tester", "tester@test.com")]),
+        )
+        .unwrap();
+        let id = load(path.clone()).unwrap().authors()[0].id().clone();
+        assert!(!id.is_empty());
+        assert_eq!(&id, load(path).unwrap().authors()[0].id());
+    }
+
+    #[test]
+    fn test_load_keeps_ids() {
+        let path = PersistenceFilePath::Basic.get_filepath();
+        let id = load(path.clone()).unwrap().authors()[0].id().clone();
+        assert!(!id.is_empty());
+        assert_eq!(&id, load(path).unwrap().authors()[0].id());
+    }
+
+    #[test]
+    fn test_numeric_id_is_not_an_index() {
+        let path = PersistenceFilePath::NumericId.get_filepath();
+        let authors = load(path).unwrap();
+        assert_eq!(Some(0), authors.find_author_index("00000001"));
+        assert_eq!(Some(1), authors.find_author_index("1"));
+    }
+
+    #[test]
+    fn test_load_teams_existing() {
+        let path = PersistenceFilePath::Teams.get_filepath();
+        let teams = load_teams(path).unwrap();
+        assert_eq!(1, teams.find_team("core").unwrap().members().len());
+    }
+
+    #[test]
+    fn test_write_and_load_teams() {
+        let path = PersistenceFilePath::WritableTeams.get_filepath();
+        let mut team = Team::new("core");
+        team.add_member("tester@test.com");
+        save_teams(path.clone(), TeamCollection::from(vec![team])).unwrap();
+        let teams = load_teams(path).unwrap();
+        assert!(teams.find_team("core").is_some());
+    }
+
+    #[test]
+    fn test_write_and_load_branch_pairs() {
+        let path = PersistenceFilePath::WritableBranchPairs.get_filepath();
+        let pair = BranchPair::new("feature/*", vec!["0a1b2c3d".to_string()]);
+        save_branch_pairs(path.clone(), BranchPairCollection::from(vec![pair.clone()])).unwrap();
+        let pairs = load_branch_pairs(path).unwrap();
+        assert_eq!(Some(&pair), pairs.find_for("feature/login"));
+    }
+
+    #[test]
+    fn test_write_and_load_profiles() {
+        let path = PersistenceFilePath::WritableProfiles.get_filepath();
+        let mut profile = Profile::new("work");
+        profile.add_directory("~/work");
+        save_profiles(path.clone(), ProfileCollection::from(vec![profile.clone()])).unwrap();
+        assert_eq!(
+            Some(&profile),
+            load_profiles(path).unwrap().find_profile("work")
+        );
+    }
+
+    #[test]
+    fn test_write_and_load_session() {
+        let path = PersistenceFilePath::WritableSession.get_filepath();
+        let session = Session::start(Utc::now());
+        save_session(path.clone(), &session).unwrap();
+        assert_eq!(session, load_session(path).unwrap());
+    }
+
+    #[test]
+    fn test_write_and_load_active() {
+        let path = PersistenceFilePath::WritableActive.get_filepath();
+        assert_eq!(
+            None,
+            load_active(PersistenceFilePath::Missing.get_filepath()).unwrap()
+        );
+        save_active(path.clone(), &["0a1b2c3d".to_string()]).unwrap();
+        assert_eq!(
+            Some(vec!["0a1b2c3d".to_string()]),
+            load_active(path).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_load_session_missing() {
+        let path = PersistenceFilePath::Missing.get_filepath();
+        assert_eq!(Session::default(), load_session(path).unwrap());
+    }
+
+    fn load_settings(paths: &[PathBuf]) -> Settings {
+        let mut layers = SettingsLayers::new();
+        for path in paths {
+            let layer = load_settings_layer(path.clone()).unwrap();
+            layers
+                .push(SettingOrigin::File(path.clone()), layer)
+                .unwrap();
+        }
+        layers.settings().unwrap()
+    }
+
+    #[test]
+    fn test_load_settings_missing() {
+        let path = PersistenceFilePath::Missing.get_filepath();
+        assert_eq!(Settings::default(), load_settings(&[path]));
+    }
+
+    #[test]
+    fn test_load_settings_existing() {
+        let path = PersistenceFilePath::Settings.get_filepath();
+        let settings = load_settings(&[path]);
+        assert_eq!(HookPolicy::Replace, settings.hook.commit);
+    }
+
+    #[test]
+    fn test_load_settings_layers_project_over_global() {
+        let mut layers = SettingsLayers::new();
+        let path = PersistenceFilePath::Settings.get_filepath();
+        let layer = load_settings_layer(path.clone()).unwrap();
+        layers.push(SettingOrigin::File(path), layer).unwrap();
+        let path = PersistenceFilePath::Project.get_filepath();
+        let layer = load_settings_layer(path.clone()).unwrap();
+        layers
+            .push_project(SettingOrigin::File(path), layer)
+            .unwrap();
+        let settings = layers.settings().unwrap();
+        assert_eq!(HookPolicy::Replace, settings.hook.commit);
+        assert_eq!(HookPolicy::Append, settings.hook.merge);
+        assert_eq!("Paired-with", settings.trailer.key);
+    }
+
+    #[test]
+    fn test_write_and_load_settings_layer() {
+        let path = PersistenceFilePath::WritableSettings.get_filepath();
+        let layer = serde_yaml::from_str("trailer:\n  order: name").unwrap();
+        save_settings_layer(path.clone(), &layer).unwrap();
+        assert_eq!(layer, load_settings_layer(path).unwrap());
+    }
+
+    #[test]
+    fn test_load_project() {
+        let project = load_project(PersistenceFilePath::Project.get_filepath()).unwrap();
+        assert_eq!("project@test.com", project.authors[0].email);
+    }
+
     #[test]
     fn test_save_directory_exists_missing() {
         let file_path: PathBuf = PersistenceFilePath::MissingParent.get_filepath();